@@ -1,4 +1,5 @@
 use encoding_rs::Encoding;
+use hanconv::Converters;
 use serde::Serialize;
 use std::fs::File;
 use std::io::Read;
@@ -11,73 +12,31 @@ use {
 };
 
 #[tauri::command]
-async fn s2t(s: String) -> String {
-    hanconv::s2t(s)
+async fn convert(conversion: Converters, s: String) -> String {
+    conversion.new_converter().convert(s)
 }
 
-#[tauri::command]
-async fn t2s(s: String) -> String {
-    hanconv::t2s(s)
-}
-
-#[tauri::command]
-async fn s2tw(s: String) -> String {
-    hanconv::s2tw(s)
-}
-
-#[tauri::command]
-async fn tw2s(s: String) -> String {
-    hanconv::tw2s(s)
-}
-
-#[tauri::command]
-async fn s2twp(s: String) -> String {
-    hanconv::s2twp(s)
+/// A preset as listed by the conversion selector of the frontend.
+#[derive(Serialize)]
+struct Conversion {
+    name: Converters,
+    /// The BCP 47 tags of the source and target locales, e.g. `zh-Hant-TW`.
+    source: &'static str,
+    target: &'static str,
+    idiom: bool,
 }
 
 #[tauri::command]
-async fn tw2sp(s: String) -> String {
-    hanconv::tw2sp(s)
-}
-
-#[tauri::command]
-async fn t2tw(s: String) -> String {
-    hanconv::t2tw(s)
-}
-
-#[tauri::command]
-async fn tw2t(s: String) -> String {
-    hanconv::tw2t(s)
-}
-
-#[tauri::command]
-async fn s2hk(s: String) -> String {
-    hanconv::s2hk(s)
-}
-
-#[tauri::command]
-async fn hk2s(s: String) -> String {
-    hanconv::hk2s(s)
-}
-
-#[tauri::command]
-async fn t2hk(s: String) -> String {
-    hanconv::t2hk(s)
-}
-
-#[tauri::command]
-async fn hk2t(s: String) -> String {
-    hanconv::hk2t(s)
-}
-
-#[tauri::command]
-async fn t2jp(s: String) -> String {
-    hanconv::t2jp(s)
-}
-
-#[tauri::command]
-async fn jp2t(s: String) -> String {
-    hanconv::jp2t(s)
+fn conversions() -> Vec<Conversion> {
+    Converters::ALL
+        .iter()
+        .map(|&converters| Conversion {
+            name: converters,
+            source: converters.source().tag,
+            target: converters.target().tag,
+            idiom: converters.idiom(),
+        })
+        .collect()
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -141,20 +100,8 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            s2t,
-            t2s,
-            s2tw,
-            tw2s,
-            s2twp,
-            tw2sp,
-            t2tw,
-            tw2t,
-            s2hk,
-            hk2s,
-            t2hk,
-            hk2t,
-            t2jp,
-            jp2t,
+            convert,
+            conversions,
             read_text_file,
         ])
         .run(tauri::generate_context!())
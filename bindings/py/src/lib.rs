@@ -1,90 +1,181 @@
+use ::hanconv::Converters;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
+/// Converts `s` with the preset named `conversion`, e.g. "s2twp".
+#[pyfunction]
+fn convert(s: &str, conversion: &str) -> PyResult<String> {
+    let converters = conversion
+        .parse::<Converters>()
+        .map_err(|err| PyValueError::new_err(format!("{err}: {conversion}")))?;
+
+    Ok(converters.new_converter().convert(s))
+}
+
+/// Returns the names of the available presets.
+#[pyfunction]
+fn conversions() -> Vec<&'static str> {
+    Converters::ALL
+        .iter()
+        .map(|converters| converters.name())
+        .collect()
+}
+
+#[cfg(feature = "st")]
 #[pyfunction]
 fn s2t(s: &str) -> PyResult<String> {
     Ok(::hanconv::s2t(s))
 }
 
+#[cfg(feature = "ts")]
 #[pyfunction]
 fn t2s(s: &str) -> PyResult<String> {
     Ok(::hanconv::t2s(s))
 }
 
+#[cfg(all(feature = "st", feature = "tw"))]
 #[pyfunction]
 fn s2tw(s: &str) -> PyResult<String> {
     Ok(::hanconv::s2tw(s))
 }
 
+#[cfg(all(feature = "tw", feature = "ts"))]
 #[pyfunction]
 fn tw2s(s: &str) -> PyResult<String> {
     Ok(::hanconv::tw2s(s))
 }
 
+#[cfg(all(feature = "st", feature = "tw"))]
 #[pyfunction]
 fn s2twp(s: &str) -> PyResult<String> {
     Ok(::hanconv::s2twp(s))
 }
 
+#[cfg(all(feature = "tw", feature = "ts"))]
 #[pyfunction]
 fn tw2sp(s: &str) -> PyResult<String> {
     Ok(::hanconv::tw2sp(s))
 }
 
+#[cfg(feature = "tw")]
 #[pyfunction]
 fn t2tw(s: &str) -> PyResult<String> {
     Ok(::hanconv::t2tw(s))
 }
 
+#[cfg(feature = "tw")]
 #[pyfunction]
 fn tw2t(s: &str) -> PyResult<String> {
     Ok(::hanconv::tw2t(s))
 }
 
+#[cfg(all(feature = "st", feature = "hk"))]
 #[pyfunction]
 fn s2hk(s: &str) -> PyResult<String> {
     Ok(::hanconv::s2hk(s))
 }
 
+#[cfg(all(feature = "hk", feature = "ts"))]
 #[pyfunction]
 fn hk2s(s: &str) -> PyResult<String> {
     Ok(::hanconv::hk2s(s))
 }
 
+#[cfg(feature = "hk")]
 #[pyfunction]
 fn t2hk(s: &str) -> PyResult<String> {
     Ok(::hanconv::t2hk(s))
 }
 
+#[cfg(feature = "hk")]
 #[pyfunction]
 fn hk2t(s: &str) -> PyResult<String> {
     Ok(::hanconv::hk2t(s))
 }
 
+#[cfg(feature = "jp")]
 #[pyfunction]
 fn t2jp(s: &str) -> PyResult<String> {
     Ok(::hanconv::t2jp(s))
 }
 
+#[cfg(feature = "jp")]
 #[pyfunction]
 fn jp2t(s: &str) -> PyResult<String> {
     Ok(::hanconv::jp2t(s))
 }
 
+#[cfg(all(feature = "hk", feature = "tw"))]
+#[pyfunction]
+fn hk2tw(s: &str) -> PyResult<String> {
+    Ok(::hanconv::hk2tw(s))
+}
+
+#[cfg(all(feature = "tw", feature = "hk"))]
+#[pyfunction]
+fn tw2hk(s: &str) -> PyResult<String> {
+    Ok(::hanconv::tw2hk(s))
+}
+
+#[cfg(all(feature = "hk", feature = "tw"))]
+#[pyfunction]
+fn hk2twp(s: &str) -> PyResult<String> {
+    Ok(::hanconv::hk2twp(s))
+}
+
+#[cfg(all(feature = "st", feature = "jp"))]
+#[pyfunction]
+fn s2jp(s: &str) -> PyResult<String> {
+    Ok(::hanconv::s2jp(s))
+}
+
+#[cfg(all(feature = "jp", feature = "ts"))]
+#[pyfunction]
+fn jp2s(s: &str) -> PyResult<String> {
+    Ok(::hanconv::jp2s(s))
+}
+
 #[pymodule]
 fn hanconv(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(convert, m)?)?;
+    m.add_function(wrap_pyfunction!(conversions, m)?)?;
+    #[cfg(feature = "st")]
     m.add_function(wrap_pyfunction!(s2t, m)?)?;
+    #[cfg(feature = "ts")]
     m.add_function(wrap_pyfunction!(t2s, m)?)?;
+    #[cfg(all(feature = "st", feature = "tw"))]
     m.add_function(wrap_pyfunction!(s2tw, m)?)?;
+    #[cfg(all(feature = "tw", feature = "ts"))]
     m.add_function(wrap_pyfunction!(tw2s, m)?)?;
+    #[cfg(all(feature = "st", feature = "tw"))]
     m.add_function(wrap_pyfunction!(s2twp, m)?)?;
+    #[cfg(all(feature = "tw", feature = "ts"))]
     m.add_function(wrap_pyfunction!(tw2sp, m)?)?;
+    #[cfg(feature = "tw")]
     m.add_function(wrap_pyfunction!(t2tw, m)?)?;
+    #[cfg(feature = "tw")]
     m.add_function(wrap_pyfunction!(tw2t, m)?)?;
+    #[cfg(all(feature = "st", feature = "hk"))]
     m.add_function(wrap_pyfunction!(s2hk, m)?)?;
+    #[cfg(all(feature = "hk", feature = "ts"))]
     m.add_function(wrap_pyfunction!(hk2s, m)?)?;
+    #[cfg(feature = "hk")]
     m.add_function(wrap_pyfunction!(t2hk, m)?)?;
+    #[cfg(feature = "hk")]
     m.add_function(wrap_pyfunction!(hk2t, m)?)?;
+    #[cfg(feature = "jp")]
     m.add_function(wrap_pyfunction!(t2jp, m)?)?;
+    #[cfg(feature = "jp")]
     m.add_function(wrap_pyfunction!(jp2t, m)?)?;
+    #[cfg(all(feature = "hk", feature = "tw"))]
+    m.add_function(wrap_pyfunction!(hk2tw, m)?)?;
+    #[cfg(all(feature = "tw", feature = "hk"))]
+    m.add_function(wrap_pyfunction!(tw2hk, m)?)?;
+    #[cfg(all(feature = "hk", feature = "tw"))]
+    m.add_function(wrap_pyfunction!(hk2twp, m)?)?;
+    #[cfg(all(feature = "st", feature = "jp"))]
+    m.add_function(wrap_pyfunction!(s2jp, m)?)?;
+    #[cfg(all(feature = "jp", feature = "ts"))]
+    m.add_function(wrap_pyfunction!(jp2s, m)?)?;
     Ok(())
 }
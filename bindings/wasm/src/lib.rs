@@ -1,71 +1,132 @@
+use hanconv::Converters;
 use wasm_bindgen::prelude::*;
 
+/// Converts `s` with the preset named `conversion`, e.g. "s2twp".
+#[wasm_bindgen]
+pub fn convert(s: &str, conversion: &str) -> Result<String, JsError> {
+    let converters = conversion.parse::<Converters>()?;
+    Ok(converters.new_converter().convert(s))
+}
+
+/// Returns the names of the available presets.
+#[wasm_bindgen]
+pub fn conversions() -> Vec<String> {
+    Converters::ALL
+        .iter()
+        .map(|converters| converters.to_string())
+        .collect()
+}
+
+#[cfg(feature = "st")]
 #[wasm_bindgen]
 pub fn s2t(s: &str) -> String {
     hanconv::s2t(s)
 }
 
+#[cfg(feature = "ts")]
 #[wasm_bindgen]
 pub fn t2s(s: &str) -> String {
     hanconv::t2s(s)
 }
 
+#[cfg(all(feature = "st", feature = "tw"))]
 #[wasm_bindgen]
 pub fn s2tw(s: &str) -> String {
     hanconv::s2tw(s)
 }
 
+#[cfg(all(feature = "tw", feature = "ts"))]
 #[wasm_bindgen]
 pub fn tw2s(s: &str) -> String {
     hanconv::tw2s(s)
 }
 
+#[cfg(all(feature = "st", feature = "tw"))]
 #[wasm_bindgen]
 pub fn s2twp(s: &str) -> String {
     hanconv::s2twp(s)
 }
 
+#[cfg(all(feature = "tw", feature = "ts"))]
 #[wasm_bindgen]
 pub fn tw2sp(s: &str) -> String {
     hanconv::tw2sp(s)
 }
 
+#[cfg(feature = "tw")]
 #[wasm_bindgen]
 pub fn t2tw(s: &str) -> String {
     hanconv::t2tw(s)
 }
 
+#[cfg(feature = "tw")]
 #[wasm_bindgen]
 pub fn tw2t(s: &str) -> String {
     hanconv::tw2t(s)
 }
 
+#[cfg(all(feature = "st", feature = "hk"))]
 #[wasm_bindgen]
 pub fn s2hk(s: &str) -> String {
     hanconv::s2hk(s)
 }
 
+#[cfg(all(feature = "hk", feature = "ts"))]
 #[wasm_bindgen]
 pub fn hk2s(s: &str) -> String {
     hanconv::hk2s(s)
 }
 
+#[cfg(feature = "hk")]
 #[wasm_bindgen]
 pub fn t2hk(s: &str) -> String {
     hanconv::t2hk(s)
 }
 
+#[cfg(feature = "hk")]
 #[wasm_bindgen]
 pub fn hk2t(s: &str) -> String {
     hanconv::hk2t(s)
 }
 
+#[cfg(feature = "jp")]
 #[wasm_bindgen]
 pub fn t2jp(s: &str) -> String {
     hanconv::t2jp(s)
 }
 
+#[cfg(feature = "jp")]
 #[wasm_bindgen]
 pub fn jp2t(s: &str) -> String {
     hanconv::jp2t(s)
 }
+
+#[cfg(all(feature = "hk", feature = "tw"))]
+#[wasm_bindgen]
+pub fn hk2tw(s: &str) -> String {
+    hanconv::hk2tw(s)
+}
+
+#[cfg(all(feature = "tw", feature = "hk"))]
+#[wasm_bindgen]
+pub fn tw2hk(s: &str) -> String {
+    hanconv::tw2hk(s)
+}
+
+#[cfg(all(feature = "hk", feature = "tw"))]
+#[wasm_bindgen]
+pub fn hk2twp(s: &str) -> String {
+    hanconv::hk2twp(s)
+}
+
+#[cfg(all(feature = "st", feature = "jp"))]
+#[wasm_bindgen]
+pub fn s2jp(s: &str) -> String {
+    hanconv::s2jp(s)
+}
+
+#[cfg(all(feature = "jp", feature = "ts"))]
+#[wasm_bindgen]
+pub fn jp2s(s: &str) -> String {
+    hanconv::jp2s(s)
+}
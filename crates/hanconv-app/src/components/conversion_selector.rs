@@ -2,7 +2,6 @@ use crate::conversion::Conversion;
 use gpui::{App, Corner, FocusHandle, IntoElement, RenderOnce, Window};
 use gpui_component::button::Button;
 use gpui_component::menu::DropdownMenu;
-use strum::VariantArray;
 
 #[derive(IntoElement)]
 pub struct ConversionSelector {
@@ -45,8 +44,8 @@ impl RenderOnce for ConversionSelector {
                         menu = menu.action_context(action_context);
                     }
 
-                    for conversions in Conversion::VARIANTS.chunks(2) {
-                        for &conversion in conversions {
+                    for conversions in Conversion::pairs() {
+                        for conversion in conversions {
                             menu = menu.menu_with_check(
                                 conversion.title(),
                                 self.selected == conversion,
@@ -4,6 +4,7 @@ use anyhow::anyhow;
 use dirs::{config_local_dir, document_dir};
 use gpui::{Context, EventEmitter, SharedString};
 use gpui_component::{Theme, ThemeRegistry};
+use hanconv::Converters;
 use icu_locale::fallback::{LocaleFallbackConfig, LocaleFallbackPriority};
 use icu_locale::{locale, DataLocale, Locale, LocaleFallbacker};
 use rust_i18n::set_locale;
@@ -32,7 +33,7 @@ impl Default for Config {
         Config {
             app_name: env!("CARGO_PKG_NAME").to_string(),
             locale: None,
-            conversion: Conversion::S2T,
+            conversion: Conversion(Converters::ALL[0]),
             theme: None,
             last_directory: None,
             themes_directory: None,
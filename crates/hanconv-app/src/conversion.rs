@@ -1,53 +1,41 @@
 use gpui::Action;
-use schemars::JsonSchema;
+use hanconv::Converters;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Serialize};
-use strum::{EnumCount, EnumIter, VariantArray};
+use std::borrow::Cow;
 
-#[derive(
-    Debug,
-    Copy,
-    Clone,
-    PartialEq,
-    EnumCount,
-    EnumIter,
-    VariantArray,
-    Serialize,
-    Deserialize,
-    JsonSchema,
-    Action,
-)]
-#[serde(rename_all = "lowercase")]
-pub enum Conversion {
-    S2T,
-    T2S,
-    S2TW,
-    TW2S,
-    T2TW,
-    TW2T,
-    S2HK,
-    HK2S,
-    T2HK,
-    HK2T,
-    T2JP,
-    JP2T,
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Action)]
+#[serde(transparent)]
+pub struct Conversion(pub Converters);
+
+impl JsonSchema for Conversion {
+    fn schema_name() -> Cow<'static, str> {
+        "Conversion".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let names = Converters::ALL
+            .iter()
+            .map(|converters| converters.name())
+            .collect::<Vec<_>>();
+
+        json_schema!({
+            "type": "string",
+            "enum": names,
+        })
+    }
 }
 
 impl Conversion {
+    /// Returns the conversions enabled in the library, each followed by its reverse if it has one.
+    pub fn pairs() -> impl Iterator<Item = impl Iterator<Item = Conversion>> {
+        Converters::ALL
+            .chunk_by(|a, b| a.reverse() == Some(*b))
+            .map(|pair| pair.iter().copied().map(Conversion))
+    }
+
     pub fn name(&self) -> &'static str {
-        match self {
-            Conversion::S2T => "s2t",
-            Conversion::T2S => "t2s",
-            Conversion::S2TW => "s2tw",
-            Conversion::TW2S => "tw2s",
-            Conversion::T2TW => "t2tw",
-            Conversion::TW2T => "tw2t",
-            Conversion::S2HK => "s2hk",
-            Conversion::HK2S => "hk2s",
-            Conversion::T2HK => "t2hk",
-            Conversion::HK2T => "hk2t",
-            Conversion::T2JP => "t2jp",
-            Conversion::JP2T => "jp2t",
-        }
+        self.0.name()
     }
 
     pub fn title(&self) -> String {
@@ -59,19 +47,6 @@ impl Conversion {
     }
 
     pub fn run(&self, content: impl AsRef<str>) -> String {
-        match self {
-            Conversion::S2T => hanconv::s2t(content),
-            Conversion::T2S => hanconv::t2s(content),
-            Conversion::S2TW => hanconv::s2tw(content),
-            Conversion::TW2S => hanconv::tw2s(content),
-            Conversion::T2TW => hanconv::t2tw(content),
-            Conversion::TW2T => hanconv::tw2t(content),
-            Conversion::S2HK => hanconv::s2hk(content),
-            Conversion::HK2S => hanconv::hk2s(content),
-            Conversion::T2HK => hanconv::t2hk(content),
-            Conversion::HK2T => hanconv::hk2t(content),
-            Conversion::T2JP => hanconv::t2jp(content),
-            Conversion::JP2T => hanconv::jp2t(content),
-        }
+        self.0.new_converter().convert(content)
     }
 }
@@ -24,12 +24,12 @@ use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::{Input, InputEvent, InputState};
 use gpui_component::menu::AppMenuBar;
 use gpui_component::{gray_500, ActiveTheme, Root, Sizable, ThemeRegistry, TitleBar};
+use hanconv::Converters;
 use icu_locale::Locale;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use strum::{EnumCount, VariantArray};
 use unicode_segmentation::UnicodeSegmentation;
 
 i18n!("locales", fallback = "en");
@@ -171,12 +171,11 @@ impl Hanconv {
     }
 
     fn conversion_menu(&self, cx: &App) -> Menu {
-        let chunks = Conversion::VARIANTS.chunks(2);
-        let mut conversion_menu_items =
-            Vec::with_capacity(Conversion::COUNT + (Conversion::COUNT - 1) / 2);
+        let count = Converters::ALL.len();
+        let mut conversion_menu_items = Vec::with_capacity(count + count.saturating_sub(1) / 2);
 
-        for conversions in chunks {
-            conversion_menu_items.extend(conversions.iter().cloned().map(|conversion| {
+        for conversions in Conversion::pairs() {
+            conversion_menu_items.extend(conversions.map(|conversion| {
                 MenuItem::action(conversion.title(), conversion)
                     .checked(self.config.read(cx).conversion() == conversion)
             }));
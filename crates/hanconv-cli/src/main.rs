@@ -1,7 +1,8 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{ArgMatches, Args, Command, FromArgMatches, Parser, Subcommand};
 use encoding_rs::{Encoding, UTF_8};
-use hanconv::{Converter, Converters::*};
+use hanconv::{ConvertReader, Converter, Converters};
 use rayon::prelude::*;
+use serde_json::json;
 use std::borrow::Cow;
 use std::error::Error;
 use std::fs::File;
@@ -11,94 +12,93 @@ use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(version, about)]
-struct CLI {
+struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Subcommands,
 }
 
-impl CLI {
+impl Cli {
     fn run(self) -> Result<(), Box<dyn Error>> {
         self.command.run()
     }
 }
 
+/// A conversion named after its preset, e.g. `hanconv s2t`, or one of the other commands.
+enum Subcommands {
+    Convert(Converters, Box<Conversion>),
+    Other(Commands),
+}
+
+impl Subcommands {
+    fn run(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Subcommands::Convert(converters, conversion) => {
+                conversion.run(converters.new_converter())
+            }
+            Subcommands::Other(command) => command.run(),
+        }
+    }
+}
+
+impl FromArgMatches for Subcommands {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        if let Some((name, matches)) = matches.subcommand()
+            && let Ok(converters) = name.parse()
+        {
+            let conversion = Conversion::from_arg_matches(matches)?;
+            return Ok(Subcommands::Convert(converters, Box::new(conversion)));
+        }
+
+        Ok(Subcommands::Other(Commands::from_arg_matches(matches)?))
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        *self = Subcommands::from_arg_matches(matches)?;
+        Ok(())
+    }
+}
+
+impl Subcommand for Subcommands {
+    fn augment_subcommands(command: Command) -> Command {
+        let command = Converters::ALL.iter().fold(command, |command, converters| {
+            let (english, chinese) = converters.descriptions();
+
+            command.subcommand(Conversion::augment_args(
+                Command::new(converters.name())
+                    .about(format!("Convert {english}"))
+                    .long_about(format!("Convert {english}\n\n{chinese}")),
+            ))
+        });
+
+        Commands::augment_subcommands(command)
+    }
+
+    fn augment_subcommands_for_update(command: Command) -> Command {
+        Subcommands::augment_subcommands(command)
+    }
+
+    fn has_subcommand(name: &str) -> bool {
+        name.parse::<Converters>().is_ok() || Commands::has_subcommand(name)
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    /// Convert Simplified Chinese to Traditional Chinese
-    ///
-    /// 简体中文 → 繁体中文
-    S2T(Conversion),
-    /// Convert Traditional Chinese to Simplified Chinese
+    /// List the chars converted to one of several candidates without a phrase resolving them
     ///
-    /// 繁体中文 → 简体中文
-    T2S(Conversion),
-    /// Convert Simplified Chinese to Traditional Chinese (Taiwan)
+    /// 列出有多个候选且未由词组确定转换结果的字
+    Ambiguities(Ambiguities),
+    /// Convert forth and back, listing the parts that differ as JSON lines
     ///
-    /// 简体中文 → 繁体中文（台湾）
-    S2TW(Conversion),
-    /// Convert Traditional Chinese (Taiwan) to Simplified Chinese
-    ///
-    /// 繁体中文（台湾）→ 简体中文
-    TW2S(Conversion),
-    /// Convert Simplified Chinese to Traditional Chinese (Taiwan) with Taiwanese idiom
-    ///
-    /// 简体中文 → 繁体中文（台湾），转换为台湾常用词
-    S2TWP(Conversion),
-    /// Convert Traditional Chinese (Taiwan) to Simplified Chinese with Mainland Chinese idiom
-    ///
-    /// 繁体中文（台湾）→ 简体中文，转换为中国大陆常用词
-    TW2SP(Conversion),
-    /// Convert Traditional Chinese to Traditional Chinese (Taiwan)
-    ///
-    /// 繁体中文 → 繁体中文（台湾）
-    T2TW(Conversion),
-    /// Convert Traditional Chinese (Taiwan) to Traditional Chinese
-    ///
-    /// 繁体中文（台湾）→ 繁体中文
-    TW2T(Conversion),
-    /// Convert Simplified Chinese to Traditional Chinese (Hong Kong)
-    ///
-    /// 简体中文 → 繁体中文（香港）
-    S2HK(Conversion),
-    /// Convert Traditional Chinese (Hong Kong) to Simplified Chinese
-    ///
-    /// 繁体中文（香港）→ 简体中文
-    HK2S(Conversion),
-    /// Convert Traditional Chinese to Traditional Chinese (Hong Kong)
-    ///
-    /// 繁体中文 → 繁体中文（香港）
-    T2HK(Conversion),
-    /// Convert Traditional Chinese (Hong Kong) to Traditional Chinese
-    ///
-    /// 繁体中文（香港）→ 繁体中文
-    HK2T(Conversion),
-    /// Convert Traditional Chinese characters (Kyūjitai) to New Japanese Kanji (Shinjitai)
-    ///
-    /// 繁体字 → 日文新字体
-    T2JP(Conversion),
-    /// Convert New Japanese Kanji (Shinjitai) to Traditional Chinese characters (Kyūjitai)
-    ///
-    /// 日文新字体 → 繁体字
-    JP2T(Conversion),
+    /// 往返转换，以 JSON Lines 列出无法还原的部分
+    RoundTrip(RoundTrip),
 }
 
 impl Commands {
     fn run(self) -> Result<(), Box<dyn Error>> {
         match self {
-            Commands::S2T(conversion) => conversion.run(S2T.new_converter()),
-            Commands::T2S(conversion) => conversion.run(T2S.new_converter()),
-            Commands::S2TW(conversion) => conversion.run(S2TW.new_converter()),
-            Commands::TW2S(conversion) => conversion.run(TW2S.new_converter()),
-            Commands::S2TWP(conversion) => conversion.run(S2TWP.new_converter()),
-            Commands::TW2SP(conversion) => conversion.run(TW2SP.new_converter()),
-            Commands::T2TW(conversion) => conversion.run(T2TW.new_converter()),
-            Commands::TW2T(conversion) => conversion.run(TW2T.new_converter()),
-            Commands::S2HK(conversion) => conversion.run(S2HK.new_converter()),
-            Commands::HK2S(conversion) => conversion.run(HK2S.new_converter()),
-            Commands::T2HK(conversion) => conversion.run(T2HK.new_converter()),
-            Commands::HK2T(conversion) => conversion.run(HK2T.new_converter()),
-            Commands::T2JP(conversion) => conversion.run(T2JP.new_converter()),
-            Commands::JP2T(conversion) => conversion.run(JP2T.new_converter()),
+            Commands::Ambiguities(ambiguities) => ambiguities.run(),
+            Commands::RoundTrip(round_trip) => round_trip.run(),
         }
     }
 }
@@ -146,7 +146,7 @@ struct Conversion {
 
 impl Conversion {
     fn handle_texts(self) -> Result<(), Box<dyn Error>> {
-        let converter = self.converter.unwrap_or_else(|| T2S.new_converter());
+        let converter = self.converter.as_ref().unwrap();
 
         if let Some(ref texts) = self.texts {
             let mut writer = BufWriter::new(io::stdout());
@@ -257,27 +257,25 @@ impl Conversion {
         let mut input = self.input()?;
         let mut output = self.output()?;
 
-        let mut buffer = Vec::new();
-        input.read_to_end(&mut buffer)?;
-
         let converter = self.converter.as_ref().unwrap();
 
-        let s = if self.use_default_encoding() {
-            Cow::Owned(String::from_utf8(buffer)?)
-        } else {
-            self.decode(&buffer)?
-        };
+        // UTF-8 text is converted as it is read, so files of any size use bounded memory.
+        if self.use_default_encoding() {
+            io::copy(&mut ConvertReader::new(converter, input), &mut output)?;
+            output.flush()?;
+            return Ok(());
+        }
 
-        let s = s
+        let mut buffer = Vec::new();
+        input.read_to_end(&mut buffer)?;
+
+        let s = self
+            .decode(&buffer)?
             .par_split_inclusive('\n')
             .map(|s| converter.convert(s))
             .collect::<String>();
 
-        if self.use_default_encoding() {
-            output.write_all(s.as_bytes())?;
-        } else {
-            output.write_all(&self.encode(&s)?)?;
-        }
+        output.write_all(&self.encode(&s)?)?;
 
         Ok(())
     }
@@ -295,6 +293,163 @@ impl Conversion {
     }
 }
 
+/// Text to check, read whole.
+#[derive(Args)]
+struct Source {
+    /// Input file path (stdin if not specified)
+    #[arg(short, value_name = "PATH")]
+    input_filename: Option<String>,
+    /// Text to check directly from command line
+    #[arg(value_name = "TEXT", conflicts_with = "input_filename")]
+    texts: Option<Vec<String>>,
+}
+
+impl Source {
+    fn read(&self) -> Result<String, Box<dyn Error>> {
+        if let Some(ref texts) = self.texts {
+            return Ok(texts.concat());
+        }
+
+        let mut input: Box<dyn BufRead> = if let Some(ref filename) = self.input_filename {
+            Box::new(BufReader::new(File::open(filename)?))
+        } else {
+            Box::new(BufReader::new(io::stdin()))
+        };
+
+        let mut s = String::new();
+        input.read_to_string(&mut s)?;
+        Ok(s)
+    }
+}
+
+/// Tracks the line and column, both counted in chars from 1, of increasing byte offsets in a text.
+struct Position<'a> {
+    s: &'a str,
+    consumed: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Position<'a> {
+    fn new(s: &'a str) -> Self {
+        Position {
+            s,
+            consumed: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns the line and column of the byte offset `i`, which must not be less than the last.
+    fn at(&mut self, i: usize) -> (usize, usize) {
+        for c in self.s[self.consumed..i].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.consumed = i;
+
+        (self.line, self.column)
+    }
+}
+
+#[derive(Args)]
+struct Ambiguities {
+    /// Conversion to check, e.g. s2t
+    #[arg(value_name = "CONVERSION")]
+    converters: Converters,
+    #[command(flatten)]
+    source: Source,
+}
+
+impl Ambiguities {
+    /// Prints one line per ambiguity: the line and column of the char, the char, the chosen value
+    /// and all candidates.
+    fn run(self) -> Result<(), Box<dyn Error>> {
+        let s = self.source.read()?;
+        let converter = self.converters.new_converter();
+        let mut position = Position::new(&s);
+        let mut writer = BufWriter::new(io::stdout());
+
+        for candidates in converter.ambiguities(&s) {
+            let (line, column) = position.at(candidates.span.source.start);
+
+            writeln!(
+                writer,
+                "{line}:{column}\t{}\t{}\t{}",
+                &s[candidates.span.source],
+                candidates.values[0],
+                candidates.values.join(" ")
+            )?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct RoundTrip {
+    /// Conversion forth, e.g. s2t
+    #[arg(value_name = "FORWARD")]
+    forward: Converters,
+    /// Conversion back, e.g. t2s
+    #[arg(value_name = "BACKWARD")]
+    backward: Converters,
+    #[command(flatten)]
+    source: Source,
+}
+
+impl RoundTrip {
+    /// Prints one JSON object per line for each part of the text that does not survive the round
+    /// trip, and fails if there is any.
+    fn run(self) -> Result<(), Box<dyn Error>> {
+        let s = self.source.read()?;
+        let differences = hanconv::round_trip(
+            &self.forward.new_converter(),
+            &self.backward.new_converter(),
+            &s,
+        );
+        let mut position = Position::new(&s);
+        let mut writer = BufWriter::new(io::stdout());
+
+        let entries = |matches: &[(String, Vec<String>)]| {
+            matches
+                .iter()
+                .map(|(key, values)| json!({ "key": key, "values": values }))
+                .collect::<Vec<_>>()
+        };
+
+        for difference in &differences {
+            let (line, column) = position.at(difference.source.start);
+            let object = json!({
+                "line": line,
+                "column": column,
+                "start": difference.source.start,
+                "end": difference.source.end,
+                "original": difference.original,
+                "converted": difference.converted,
+                "restored": difference.restored,
+                "forward": entries(&difference.forward),
+                "backward": entries(&difference.backward),
+            });
+
+            writeln!(writer, "{object}")?;
+        }
+
+        writer.flush()?;
+
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("round trip differs in {} places", differences.len()).into())
+        }
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    CLI::parse().run()
+    Cli::parse().run()
 }
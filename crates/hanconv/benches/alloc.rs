@@ -0,0 +1,102 @@
+//! Compares the time and the allocations per call of the conversion APIs on short strings.
+//!
+//! Run with `cargo bench --bench alloc`.
+
+use hanconv::Converters;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ROUNDS: usize = 20_000;
+
+fn measure(name: &str, inputs: &[&str], mut f: impl FnMut(&str)) {
+    for input in inputs {
+        f(input);
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..ROUNDS {
+        for input in inputs {
+            f(input);
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let calls = (ROUNDS * inputs.len()) as f64;
+
+    println!(
+        "  {name:<16} {:>8.1} ns/call {:>6.2} allocations/call",
+        elapsed.as_nanos() as f64 / calls,
+        allocations as f64 / calls
+    );
+}
+
+fn main() {
+    let unchanged = [
+        "Hello, world!",
+        "GET /api/v1/users?id=42",
+        "2024-01-01T00:00:00Z",
+        "status: ok",
+    ];
+    let converted = ["简繁转换", "打开鼠标设置", "点击确定", "头发"];
+
+    for (name, converter) in [
+        ("S2T", Converters::S2T.new_converter()),
+        ("S2TWP", Converters::S2TWP.new_converter()),
+    ] {
+        for (kind, inputs) in [("unchanged", &unchanged), ("converted", &converted)] {
+            println!("{name}, {kind} input:");
+
+            measure("convert", inputs, |s| {
+                black_box(converter.convert(s));
+            });
+
+            let mut output = String::new();
+            measure("convert_into", inputs, |s| {
+                output.clear();
+                converter.convert_into(s, &mut output);
+                black_box(&output);
+            });
+
+            measure("convert_cow", inputs, |s| {
+                black_box(converter.convert_cow(s));
+            });
+
+            let mut output = Vec::new();
+            measure("convert_to_io", inputs, |s| {
+                output.clear();
+                converter.convert_to_io(s, &mut output).unwrap();
+                output.flush().unwrap();
+                black_box(&output);
+            });
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Measures the time of the first conversion of each preset in a fresh process, which includes
+//! inflating its dictionaries with the `compress` feature, and compares it with later conversions.
+//!
+//! Run with `cargo bench --bench cold_start` and `cargo bench --bench cold_start --features compress`.
+
+use hanconv::Converters;
+use std::env;
+use std::hint::black_box;
+use std::process::Command;
+use std::time::Instant;
+
+const RUNS: usize = 5;
+
+const TEXT: &str = "打开鼠标设置，点击确定。";
+
+/// Converts `TEXT` once with a new converter and prints the elapsed time in microseconds.
+fn run(index: usize) {
+    let start = Instant::now();
+    black_box(Converters::ALL[index].new_converter().convert(TEXT));
+    print!("{}", start.elapsed().as_micros());
+}
+
+fn main() {
+    if let Ok(index) = env::var("HANCONV_COLD_START") {
+        run(index.parse().unwrap());
+        return;
+    }
+
+    let exe = env::current_exe().unwrap();
+
+    for (index, converters) in Converters::ALL.iter().enumerate() {
+        let cold = (0..RUNS)
+            .map(|_| {
+                let output = Command::new(&exe)
+                    .env("HANCONV_COLD_START", index.to_string())
+                    .output()
+                    .unwrap();
+                String::from_utf8(output.stdout).unwrap().parse().unwrap()
+            })
+            .min()
+            .unwrap_or(0u128);
+
+        let converter = converters.new_converter();
+        converter.convert(TEXT);
+        let start = Instant::now();
+        black_box(converter.convert(TEXT));
+        let warm = start.elapsed().as_micros();
+
+        let name = format!("{converters:?}");
+        println!("  {name:<8} {cold:>8} µs cold {warm:>8} µs warm");
+    }
+}
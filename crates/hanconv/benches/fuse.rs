@@ -0,0 +1,49 @@
+//! Compares the throughput of presets with one to three stages, before and after fusing them.
+//!
+//! Run with `cargo bench --bench fuse`.
+
+use hanconv::{Converter, Converters, Dictionary};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ROUNDS: usize = 20;
+
+fn measure(name: &str, converter: &Converter, text: &str) {
+    black_box(converter.convert(text));
+
+    let start = Instant::now();
+
+    for _ in 0..ROUNDS {
+        black_box(converter.convert(text));
+    }
+
+    let elapsed = start.elapsed();
+
+    println!(
+        "  {name:<10} {} stages {:>8.1} MB/s",
+        converter.stages().count(),
+        (ROUNDS * text.len()) as f64 / elapsed.as_secs_f64() / 1e6
+    );
+}
+
+fn main() {
+    // Simplified phrases joined by punctuation, about 150 KB.
+    let text = Dictionary::STPhrases
+        .iter()
+        .step_by(4)
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>()
+        .join("，");
+
+    for converters in [
+        Converters::S2T,
+        Converters::S2TW,
+        Converters::S2TWP,
+        Converters::S2HK,
+    ] {
+        println!("{converters:?}:");
+
+        measure("sequential", &converters.new_converter(), &text);
+        measure("fused", &converters.new_converter().fuse(), &text);
+    }
+}
@@ -0,0 +1,107 @@
+extern crate alloc;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[allow(dead_code)]
+#[path = "src/dict.rs"]
+mod dict;
+
+#[allow(dead_code)]
+#[path = "src/compact/encode.rs"]
+mod encode;
+
+#[cfg(all(feature = "st", feature = "ts"))]
+#[allow(dead_code)]
+#[path = "src/detect/table.rs"]
+mod table;
+
+use dict::Dictionary;
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
+use dict::Dictionary::*;
+use encode::encode;
+
+/// Dictionaries compiled into the binary, named after the statics in `conv.rs`.
+///
+/// Entries of later dictionaries override those of earlier ones with the same key. Only the
+/// dictionaries of the enabled families are compiled.
+const DICTIONARIES: &[(&str, &[Dictionary])] = &[
+    #[cfg(feature = "st")]
+    ("S2T", &[STPhrases, STCharacters]),
+    #[cfg(feature = "ts")]
+    ("T2S", &[TSPhrases, TSCharacters]),
+    #[cfg(feature = "tw")]
+    ("T2TW_VARIANTS", &[TWVariants]),
+    #[cfg(all(feature = "tw", any(feature = "st", feature = "hk")))]
+    ("T2TW_PHRASES", &[TWPhrases]),
+    #[cfg(feature = "tw")]
+    ("TW2T_VARIANTS", &[TWVariantsRevPhrases, TWVariantsRev]),
+    #[cfg(all(feature = "tw", feature = "ts"))]
+    (
+        "TW2T_PHRASES_VARIANTS",
+        &[TWPhrasesRev, TWVariantsRevPhrases, TWVariantsRev],
+    ),
+    #[cfg(feature = "hk")]
+    ("T2HK_VARIANTS", &[HKVariants]),
+    #[cfg(feature = "hk")]
+    ("HK2T_VARIANTS", &[HKVariantsRevPhrases, HKVariantsRev]),
+    #[cfg(feature = "jp")]
+    ("T2JP_VARIANTS", &[JPVariants]),
+    #[cfg(feature = "jp")]
+    (
+        "JP2T",
+        &[JPShinjitaiPhrases, JPShinjitaiCharacters, JPVariantsRev],
+    ),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=data");
+    println!("cargo:rerun-if-changed=src/dict.rs");
+    println!("cargo:rerun-if-changed=src/compact/encode.rs");
+    println!("cargo:rerun-if-changed=src/detect/table.rs");
+
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+
+    for (name, dictionaries) in DICTIONARIES {
+        let (nodes, values) = encode(
+            dictionaries
+                .iter()
+                .flat_map(|dictionary| dictionary.var_iter()),
+        );
+
+        write(Path::new(&out_dir).join(format!("{name}.nodes")), &nodes);
+        write(
+            Path::new(&out_dir).join(format!("{name}.values")),
+            values.as_bytes(),
+        );
+    }
+
+    // The table of `detect`, as a sorted slice literal.
+    #[cfg(all(feature = "st", feature = "ts"))]
+    fs::write(
+        Path::new(&out_dir).join("EXCLUDED.rs"),
+        format!(
+            "&[{}]",
+            table::excluded()
+                .into_iter()
+                .map(|(c, mask)| format!("({c:?}, {mask}),"))
+                .collect::<String>()
+        ),
+    )
+    .unwrap();
+}
+
+/// Writes a section of a compiled dictionary, deflated with the `compress` feature.
+fn write(path: PathBuf, bytes: &[u8]) {
+    #[cfg(feature = "compress")]
+    let bytes = &miniz_oxide::deflate::compress_to_vec(bytes, 10);
+
+    fs::write(path, bytes).unwrap();
+}
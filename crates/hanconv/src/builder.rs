@@ -0,0 +1,219 @@
+use crate::conv::{CUSTOM_STAGE, Converter, Converters};
+use crate::matcher::Matcher;
+use crate::protect::Protection;
+use crate::stage::Stage;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::RangeBounds;
+
+/// Builds a [`Converter`] from a chain of dictionaries, typically starting from a preset with
+/// [`Converters::builder`](crate::Converters::builder).
+///
+/// User dictionaries can run as separate stages before or after a given stage, in which case the
+/// stages after them convert their output further, or be layered over a stage with
+/// [`overlay`](ConverterBuilder::overlay) so that their entries win over the entries of the stage.
+///
+/// Stages of several presets can be mixed with [`preset`](ConverterBuilder::preset) and
+/// [`preset_stages`](ConverterBuilder::preset_stages) for chains that no preset covers.
+#[derive(Default)]
+pub struct ConverterBuilder {
+    stages: Vec<(Cow<'static, str>, Box<dyn Stage>)>,
+    protection: Protection,
+}
+
+impl ConverterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the stages of a preset.
+    pub fn preset(self, converters: Converters) -> Self {
+        self.preset_stages(converters, ..)
+    }
+
+    /// Appends the stages of a preset in the range `stages`, as indexed in
+    /// [`Converters::stages`], e.g. `1..` of `S2TWP` for the Taiwan phrases and variants without
+    /// the conversion from Simplified Chinese.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stages` is out of bounds for the stages of the preset.
+    pub fn preset_stages(
+        mut self,
+        converters: Converters,
+        stages: impl RangeBounds<usize>,
+    ) -> Self {
+        self.stages.extend(
+            converters
+                .stages()
+                .drain(stages)
+                .map(|(name, dictionary)| (Cow::Borrowed(name), Box::new(dictionary) as _)),
+        );
+        self
+    }
+
+    /// Appends a stage.
+    pub fn push(self, stage: impl Stage + 'static) -> Self {
+        self.push_named(CUSTOM_STAGE, stage)
+    }
+
+    /// Appends a stage listed as `name` by [`Converter::stages`].
+    pub fn push_named(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        stage: impl Stage + 'static,
+    ) -> Self {
+        self.stages.push((name.into(), Box::new(stage)));
+        self
+    }
+
+    /// Inserts a stage before the stage at index `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of stages.
+    pub fn insert_before(mut self, index: usize, stage: impl Stage + 'static) -> Self {
+        self.stages
+            .insert(index, (Cow::Borrowed(CUSTOM_STAGE), Box::new(stage)));
+        self
+    }
+
+    /// Inserts a stage after the stage at index `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than the number of stages.
+    pub fn insert_after(mut self, index: usize, stage: impl Stage + 'static) -> Self {
+        assert!(index < self.stages.len(), "stage index out of bounds");
+        self.stages
+            .insert(index + 1, (Cow::Borrowed(CUSTOM_STAGE), Box::new(stage)));
+        self
+    }
+
+    /// Layers `dictionary` over the stage at index `stage`.
+    ///
+    /// The stage then matches the longest key of either dictionary, preferring the entry of
+    /// `dictionary` when both have the same key. It is listed as its name followed by `+custom`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stage` is not less than the number of stages, or if the stage is not a
+    /// dictionary.
+    pub fn overlay(mut self, stage: usize, dictionary: impl Matcher + 'static) -> Self {
+        assert!(stage < self.stages.len(), "stage index out of bounds");
+
+        let (name, base) = self.stages.remove(stage);
+        assert!(base.as_matcher().is_some(), "stage is not a dictionary");
+        self.stages.insert(
+            stage,
+            (
+                Cow::Owned(format!("{name}+{CUSTOM_STAGE}")),
+                Box::new(Overlay {
+                    top: Box::new(dictionary),
+                    base,
+                }),
+            ),
+        );
+        self
+    }
+
+    /// Copies the regions of the input found by `protection` verbatim.
+    pub fn protect(mut self, protection: Protection) -> Self {
+        self.protection = protection;
+        self
+    }
+
+    pub fn build(self) -> Converter {
+        Converter::from_stages(self.stages).protect(self.protection)
+    }
+}
+
+struct Overlay {
+    top: Box<dyn Matcher>,
+    /// A stage that is a dictionary.
+    base: Box<dyn Stage>,
+}
+
+impl Overlay {
+    fn base(&self) -> &dyn Matcher {
+        self.base.as_matcher().unwrap()
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.top
+            .longest_match(key)
+            .is_some_and(|(_, len)| len == key.len())
+    }
+}
+
+impl Matcher for Overlay {
+    fn depth(&self) -> usize {
+        self.top.depth().max(self.base().depth())
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        match (self.top.longest_match(s), self.base().longest_match(s)) {
+            (Some(top), Some(base)) if base.1 > top.1 => Some(base),
+            (None, base) => base,
+            (top, _) => top,
+        }
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        match (self.top.candidates(s), self.base().candidates(s)) {
+            (Some(top), Some(base)) if base.1 > top.1 => Some(base),
+            (None, base) => base,
+            (top, _) => top,
+        }
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        Box::new(
+            self.base()
+                .entries()
+                .filter(|(key, _)| !self.contains(key))
+                .chain(self.top.entries()),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "st"))]
+mod tests {
+    use crate::{Converters, Trie};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_builder() {
+        let glossary = Trie::from_text("# Glossary\n\n云服务\t雲端服務\n");
+
+        let converter = Converters::S2T.builder().overlay(0, glossary).build();
+        assert_eq!(converter.convert("云服务器"), "雲端服務器");
+
+        let converter = Converters::S2T
+            .builder()
+            .insert_after(0, Trie::from_iter([("鼠標", "滑鼠")]))
+            .build();
+        assert_eq!(converter.convert("鼠标"), "滑鼠");
+        assert_eq!(converter.stages().collect::<Vec<_>>(), ["S2T", "custom"]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "tw", feature = "hk"))]
+    fn test_preset_stages() {
+        use crate::ConverterBuilder;
+
+        let converter = ConverterBuilder::new()
+            .preset(Converters::HK2T)
+            .preset_stages(Converters::S2TWP, 1..)
+            .build();
+
+        assert_eq!(
+            converter.stages().collect::<Vec<_>>(),
+            ["HK2T_VARIANTS", "T2TW_PHRASES", "T2TW_VARIANTS"]
+        );
+        assert_eq!(converter.convert("户口的鼠標"), "戶口的滑鼠");
+    }
+}
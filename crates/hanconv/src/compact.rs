@@ -0,0 +1,531 @@
+mod encode;
+
+use crate::matcher::Matcher;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use core::iter;
+use core::ops::Range;
+use core::str::{self, Utf8Error};
+use encode::{HEADER_SIZE, NODE_SIZE, NONE};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// Magic bytes at the start of a compact trie file.
+pub const MAGIC: [u8; 4] = *b"HCTR";
+
+/// Version of the compact trie file format.
+pub const VERSION: u32 = 2;
+
+const FILE_HEADER_SIZE: usize = 16;
+
+/// An immutable trie stored as flat tables, queried in place without any parsing.
+///
+/// # Format
+///
+/// A compact trie file consists of a 16-byte header, the node section and the value section. All
+/// integers are little-endian `u32`s.
+///
+/// | Offset | Size | Content                                    |
+/// |-------:|-----:|:-------------------------------------------|
+/// |      0 |    4 | Magic bytes `HCTR`                         |
+/// |      4 |    4 | Format version, currently `2`              |
+/// |      8 |    4 | Length `N` of the node section in bytes    |
+/// |     12 |    4 | Length `V` of the value section in bytes   |
+/// |     16 |    N | Node section                               |
+/// | 16 + N |    V | Value section, UTF-8 text                  |
+///
+/// The node section starts with the depth of the trie, the number of nodes `n`, the number of
+/// distinct value groups `g` and the number of values `m`. It is followed by `n` node records of
+/// 12 bytes each, holding the char leading to the node, the index of its first child and the
+/// index of its value group (`0xFFFFFFFF` if the node has no value). Then come `g + 1` indices into
+/// the values, group `i` holding the values from index `i` to index `i + 1`, and `m + 1` offsets
+/// into the value section, value `j` spanning from offset `j` to offset `j + 1`.
+///
+/// The first value of a group is the one used for conversion, the others are its alternatives.
+///
+/// Node `0` is the root. Nodes are stored in breadth-first order, so the children of a node are
+/// contiguous, sorted by char, and end where the children of the next node begin.
+///
+/// # Examples
+///
+/// A file written once can be memory-mapped (for example with the `memmap2` crate) and queried
+/// in place by any number of processes:
+///
+/// ```ignore
+/// let bytes = CompactTrie::encode([("一分钟", "一分鐘")]);
+/// std::fs::write("custom.hct", bytes)?;
+///
+/// let file = std::fs::File::open("custom.hct")?;
+/// let mmap = unsafe { memmap2::Mmap::map(&file)? };
+/// let trie = CompactTrie::from_bytes(&mmap)?;
+/// assert_eq!(trie.convert("一分钟"), "一分鐘");
+/// ```
+pub struct CompactTrie<'a> {
+    nodes: &'a [u8],
+    values: &'a str,
+}
+
+/// An error reading a compact trie file or an OpenCC `.ocd2` dictionary.
+#[derive(Debug)]
+pub enum FormatError {
+    /// The data does not start with [`MAGIC`], or with the header of an `.ocd2` dictionary.
+    InvalidMagic,
+    /// The format version is not supported.
+    UnsupportedVersion(u32),
+    /// The data ends before the end of a section.
+    Truncated,
+    /// The node section, or the trie of an `.ocd2` dictionary, is inconsistent.
+    Corrupted,
+    /// The value section is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::InvalidMagic => write!(f, "invalid magic bytes"),
+            FormatError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            FormatError::Truncated => write!(f, "unexpected end of data"),
+            FormatError::Corrupted => write!(f, "corrupted node section"),
+            FormatError::InvalidUtf8(err) => write!(f, "invalid value section: {err}"),
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FormatError::InvalidUtf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<Utf8Error> for FormatError {
+    fn from(err: Utf8Error) -> Self {
+        FormatError::InvalidUtf8(err)
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+impl CompactTrie<'static> {
+    /// Encodes `(key, value)` pairs into a compact trie file.
+    ///
+    /// Later pairs override earlier ones with the same key, and empty keys are ignored.
+    pub fn encode<K: AsRef<str>, V: AsRef<str>>(
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Vec<u8> {
+        Self::encode_candidates(entries.into_iter().map(|(key, value)| (key, [value])))
+    }
+
+    /// Encodes keys and their candidate values into a compact trie file.
+    ///
+    /// The first candidate of a key is the one used for conversion. Later entries override
+    /// earlier ones with the same key, and empty keys and entries without candidates are ignored.
+    pub fn encode_candidates<K, I, V>(entries: impl IntoIterator<Item = (K, I)>) -> Vec<u8>
+    where
+        K: AsRef<str>,
+        I: IntoIterator<Item = V>,
+        V: AsRef<str>,
+    {
+        let (nodes, values) = encode::encode(entries);
+        CompactTrie::from_parts(&nodes, &values).to_bytes()
+    }
+}
+
+impl<'a> CompactTrie<'a> {
+    /// Creates a compact trie from its node section and value section without validating them.
+    ///
+    /// The sections must have been produced by the encoder, otherwise lookups may panic.
+    pub const fn from_parts(nodes: &'a [u8], values: &'a str) -> Self {
+        CompactTrie { nodes, values }
+    }
+
+    /// Borrows a compact trie from the contents of a compact trie file.
+    ///
+    /// The sections are validated but not copied, so `bytes` may come from a memory-mapped file.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, FormatError> {
+        let header = bytes
+            .get(..FILE_HEADER_SIZE)
+            .ok_or(FormatError::Truncated)?;
+
+        if header[..4] != MAGIC {
+            return Err(FormatError::InvalidMagic);
+        }
+
+        let version = read_u32(header, 4);
+        if version != VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        let nodes_len = read_u32(header, 8) as usize;
+        let values_len = read_u32(header, 12) as usize;
+
+        let nodes = bytes
+            .get(FILE_HEADER_SIZE..FILE_HEADER_SIZE + nodes_len)
+            .ok_or(FormatError::Truncated)?;
+        let values = bytes
+            .get(FILE_HEADER_SIZE + nodes_len..FILE_HEADER_SIZE + nodes_len + values_len)
+            .ok_or(FormatError::Truncated)?;
+
+        let trie = CompactTrie::from_parts(nodes, str::from_utf8(values)?);
+        trie.validate()?;
+
+        Ok(trie)
+    }
+
+    fn validate(&self) -> Result<(), FormatError> {
+        if self.nodes.len() < HEADER_SIZE {
+            return Err(FormatError::Truncated);
+        }
+
+        let len = self.len();
+        let (group_count, value_count) = (self.group_count(), self.value_count());
+
+        if len == 0
+            || self.nodes.len()
+                != HEADER_SIZE + len * NODE_SIZE + (group_count + 1 + value_count + 1) * 4
+        {
+            return Err(FormatError::Corrupted);
+        }
+
+        let mut first_child = 0;
+        for node in 0..len {
+            let next = self.first_child(node);
+            let group = self.u32_at(HEADER_SIZE + node * NODE_SIZE + 8);
+
+            // Children must come after their parent, otherwise walking the trie may never end.
+            if next < first_child
+                || next > len
+                || (next <= node && !self.children(node).is_empty())
+                || (group != NONE && group as usize >= group_count)
+            {
+                return Err(FormatError::Corrupted);
+            }
+
+            first_child = next;
+        }
+
+        let mut start = None;
+        for i in 0..=group_count {
+            let index = self.u32_at(self.groups() + i * 4) as usize;
+
+            if start.is_some_and(|start| index <= start) || index > value_count {
+                return Err(FormatError::Corrupted);
+            }
+
+            start = Some(index);
+        }
+
+        let mut start = 0;
+        for i in 0..=value_count {
+            let offset = self.u32_at(self.offsets() + i * 4) as usize;
+
+            if offset < start || !self.values.is_char_boundary(offset) {
+                return Err(FormatError::Corrupted);
+            }
+
+            start = offset;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the compact trie in the file format described above.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(FILE_HEADER_SIZE + self.nodes.len() + self.values.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.nodes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.values.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.nodes);
+        bytes.extend_from_slice(self.values.as_bytes());
+        bytes
+    }
+
+    /// Writes the compact trie in the file format described above.
+    #[cfg(feature = "std")]
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.nodes.len() as u32).to_le_bytes())?;
+        writer.write_all(&(self.values.len() as u32).to_le_bytes())?;
+        writer.write_all(self.nodes)?;
+        writer.write_all(self.values.as_bytes())
+    }
+
+    #[inline]
+    fn u32_at(&self, offset: usize) -> u32 {
+        read_u32(self.nodes, offset)
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.u32_at(0) as usize
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.u32_at(4) as usize
+    }
+
+    #[inline]
+    fn group_count(&self) -> usize {
+        self.u32_at(8) as usize
+    }
+
+    #[inline]
+    fn value_count(&self) -> usize {
+        self.u32_at(12) as usize
+    }
+
+    #[inline]
+    fn groups(&self) -> usize {
+        HEADER_SIZE + self.len() * NODE_SIZE
+    }
+
+    #[inline]
+    fn offsets(&self) -> usize {
+        self.groups() + (self.group_count() + 1) * 4
+    }
+
+    #[inline]
+    fn char_at(&self, node: usize) -> u32 {
+        self.u32_at(HEADER_SIZE + node * NODE_SIZE)
+    }
+
+    #[inline]
+    fn first_child(&self, node: usize) -> usize {
+        self.u32_at(HEADER_SIZE + node * NODE_SIZE + 4) as usize
+    }
+
+    #[inline]
+    fn children(&self, node: usize) -> Range<usize> {
+        let end = if node + 1 < self.len() {
+            self.first_child(node + 1)
+        } else {
+            self.len()
+        };
+
+        self.first_child(node)..end
+    }
+
+    fn child(&self, node: usize, c: char) -> Option<usize> {
+        let Range { mut start, mut end } = self.children(node);
+
+        // Most chars of unconverted text, such as ASCII, sort before every child of the root.
+        if start == end || (c as u32) < self.char_at(start) {
+            return None;
+        }
+
+        while start < end {
+            let mid = start + (end - start) / 2;
+
+            match self.char_at(mid).cmp(&(c as u32)) {
+                Ordering::Less => start = mid + 1,
+                Ordering::Greater => end = mid,
+                Ordering::Equal => return Some(mid),
+            }
+        }
+
+        None
+    }
+
+    fn group(&self, node: usize) -> Option<Range<usize>> {
+        let group = self.u32_at(HEADER_SIZE + node * NODE_SIZE + 8);
+        if group == NONE {
+            return None;
+        }
+
+        let offset = self.groups() + group as usize * 4;
+        Some(self.u32_at(offset) as usize..self.u32_at(offset + 4) as usize)
+    }
+
+    fn value_at(&self, index: usize) -> &'a str {
+        let offset = self.offsets() + index * 4;
+        let (start, end) = (self.u32_at(offset), self.u32_at(offset + 4));
+
+        self.values
+            .get(start as usize..end as usize)
+            .unwrap_or_default()
+    }
+
+    fn value(&self, node: usize) -> Option<&'a str> {
+        self.group(node).map(|group| self.value_at(group.start))
+    }
+
+    fn values(&self, node: usize) -> Option<Vec<&'a str>> {
+        self.group(node)
+            .map(|group| group.map(|index| self.value_at(index)).collect())
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        key.chars().try_fold(0, |node, c| self.child(node, c))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.find(key).and_then(|node| self.value(node))
+    }
+
+    /// Returns the value of `key` followed by its alternatives.
+    pub fn get_candidates(&self, key: &str) -> Option<Vec<&'a str>> {
+        self.find(key).and_then(|node| self.values(node))
+    }
+
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.find(prefix).is_some()
+    }
+
+    /// Iterates over the entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &'a str)> + '_ {
+        let mut stack = vec![(0, 0)];
+        let mut key = String::new();
+
+        iter::from_fn(move || {
+            while let Some((node, len)) = stack.pop() {
+                key.truncate(len);
+                if node != 0 {
+                    key.extend(char::from_u32(self.char_at(node)));
+                }
+
+                stack.extend(self.children(node).rev().map(|child| (child, key.len())));
+
+                if let Some(value) = self.value(node) {
+                    return Some((key.clone(), value));
+                }
+            }
+
+            None
+        })
+    }
+
+    pub fn r#match(&self, chars: impl Iterator<Item = char>) -> Option<(&'a str, usize)> {
+        let mut node = 0;
+        let mut result = None;
+
+        for (c, i) in chars.take(self.get_depth()).zip(1..) {
+            if let Some(child) = self.child(node, c) {
+                node = child;
+
+                if let Some(value) = self.value(node) {
+                    result = Some((value, i));
+                }
+            } else {
+                return result;
+            }
+        }
+
+        result
+    }
+}
+
+impl Matcher for CompactTrie<'_> {
+    fn depth(&self) -> usize {
+        self.get_depth()
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        let mut node = 0;
+        let mut result = None;
+
+        for (i, c) in s.char_indices().take(self.get_depth()) {
+            if let Some(child) = self.child(node, c) {
+                node = child;
+
+                if let Some(value) = self.value(node) {
+                    result = Some((value, i + c.len_utf8()));
+                }
+            } else {
+                return result;
+            }
+        }
+
+        result
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        let mut node = 0;
+        let mut result = None;
+
+        for (i, c) in s.char_indices().take(self.get_depth()) {
+            if let Some(child) = self.child(node, c) {
+                node = child;
+
+                if self.group(node).is_some() {
+                    result = Some((node, i + c.len_utf8()));
+                }
+            } else {
+                break;
+            }
+        }
+
+        result.and_then(|(node, len)| Some((self.values(node)?, len)))
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        Box::new(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_compact_trie() {
+        let bytes = CompactTrie::encode([("一分钟", "一分鐘"), ("一", "壹"), ("一分钟", "一分鍾")]);
+        let trie = CompactTrie::from_bytes(&bytes).unwrap();
+
+        assert_eq!(trie.get("一分钟"), Some("一分鍾"));
+        assert_eq!(trie.get_candidates("一"), Some(vec!["壹"]));
+        assert_eq!(trie.get("一分"), None);
+        assert!(trie.starts_with("一分"));
+        assert_eq!(trie.convert("一分钟一秒"), "一分鍾壹秒");
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            [("一".to_string(), "壹"), ("一分钟".to_string(), "一分鍾")]
+        );
+
+        #[cfg(feature = "std")]
+        {
+            let mut written = Vec::new();
+            trie.write(&mut written).unwrap();
+            assert_eq!(written, bytes);
+        }
+
+        assert!(matches!(
+            CompactTrie::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(FormatError::Truncated)
+        ));
+
+        let mut corrupted = bytes.clone();
+        let root = FILE_HEADER_SIZE + HEADER_SIZE;
+        corrupted[root + 4..root + 8].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(
+            CompactTrie::from_bytes(&corrupted),
+            Err(FormatError::Corrupted)
+        ));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let bytes =
+            CompactTrie::encode_candidates([("发", vec!["發", "髮"]), ("头发", vec!["頭髮"])]);
+        let trie = CompactTrie::from_bytes(&bytes).unwrap();
+
+        assert_eq!(trie.get("发"), Some("發"));
+        assert_eq!(trie.get_candidates("发"), Some(vec!["發", "髮"]));
+        assert_eq!(trie.candidates("发头发"), Some((vec!["發", "髮"], 3)));
+        assert_eq!(trie.convert("发头发"), "發頭髮");
+    }
+}
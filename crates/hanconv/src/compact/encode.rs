@@ -0,0 +1,114 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Size in bytes of the header of the node section: the depth, the number of nodes, the number of
+/// value groups and the number of values.
+pub const HEADER_SIZE: usize = 16;
+
+/// Size in bytes of a node record: the char leading to the node, the index of its first child and
+/// the index of its value group.
+pub const NODE_SIZE: usize = 12;
+
+/// Value group index of a node without a value.
+pub const NONE: u32 = u32::MAX;
+
+#[derive(Default)]
+struct Node<'a> {
+    children: BTreeMap<char, usize>,
+    values: Option<&'a [String]>,
+}
+
+/// Encodes keys and their candidate values into the node section and the value section of a
+/// compact trie.
+///
+/// Nodes are laid out in breadth-first order, so the children of a node are contiguous, sorted by
+/// char and start right after the children of the previous node. Later entries override earlier
+/// ones with the same key, and empty keys and entries without values are ignored.
+pub fn encode<K, I, V>(entries: impl IntoIterator<Item = (K, I)>) -> (Vec<u8>, String)
+where
+    K: AsRef<str>,
+    I: IntoIterator<Item = V>,
+    V: AsRef<str>,
+{
+    let entries = entries
+        .into_iter()
+        .map(|(key, values)| {
+            let values = values
+                .into_iter()
+                .map(|value| value.as_ref().to_string())
+                .collect::<Vec<_>>();
+            (key.as_ref().to_string(), values)
+        })
+        .filter(|(key, values)| !key.is_empty() && !values.is_empty())
+        .collect::<BTreeMap<_, _>>();
+
+    let mut tree = vec![Node::default()];
+    let mut depth = 0usize;
+
+    for (key, values) in &entries {
+        let mut node = 0;
+
+        for c in key.chars() {
+            node = match tree[node].children.get(&c) {
+                Some(&child) => child,
+                None => {
+                    tree.push(Node::default());
+                    let child = tree.len() - 1;
+                    tree[node].children.insert(c, child);
+                    child
+                }
+            };
+        }
+
+        depth = depth.max(key.chars().count());
+        tree[node].values = Some(values);
+    }
+
+    let mut values = String::new();
+    let mut offsets = vec![0u32];
+    let mut groups = vec![0u32];
+    let mut indices = BTreeMap::new();
+
+    let mut order = vec![(0usize, '\0')];
+    let mut records = Vec::with_capacity(tree.len() * NODE_SIZE);
+    let mut i = 0;
+
+    while i < order.len() {
+        let (node, c) = order[i];
+        let first_child = order.len();
+        order.extend(tree[node].children.iter().map(|(&c, &child)| (child, c)));
+
+        let group = match tree[node].values {
+            Some(group) => *indices.entry(group).or_insert_with(|| {
+                for value in group {
+                    values.push_str(value);
+                    offsets.push(values.len() as u32);
+                }
+                groups.push(offsets.len() as u32 - 1);
+                groups.len() as u32 - 2
+            }),
+            None => NONE,
+        };
+
+        records.extend_from_slice(&(c as u32).to_le_bytes());
+        records.extend_from_slice(&(first_child as u32).to_le_bytes());
+        records.extend_from_slice(&group.to_le_bytes());
+
+        i += 1;
+    }
+
+    let mut nodes =
+        Vec::with_capacity(HEADER_SIZE + records.len() + (groups.len() + offsets.len()) * 4);
+    nodes.extend_from_slice(&(depth as u32).to_le_bytes());
+    nodes.extend_from_slice(&(order.len() as u32).to_le_bytes());
+    nodes.extend_from_slice(&(groups.len() as u32 - 1).to_le_bytes());
+    nodes.extend_from_slice(&(offsets.len() as u32 - 1).to_le_bytes());
+    nodes.extend_from_slice(&records);
+    for offset in groups.into_iter().chain(offsets) {
+        nodes.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    (nodes, values)
+}
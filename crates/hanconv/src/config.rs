@@ -0,0 +1,263 @@
+use crate::compact::FormatError;
+use crate::conv::Converter;
+use crate::loader::{TextError, TextLoader};
+use crate::matcher::Matcher;
+use crate::trie::Trie;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The parts of an OpenCC configuration that determine the conversion.
+///
+/// The segmentation is not needed, as every dictionary of the chain matches the longest keys by
+/// itself.
+#[derive(Deserialize)]
+struct Config {
+    conversion_chain: Vec<Conversion>,
+}
+
+#[derive(Deserialize)]
+struct Conversion {
+    dict: DictConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum DictConfig {
+    Text { file: PathBuf },
+    Ocd2 { file: PathBuf },
+    Ocd { file: PathBuf },
+    Group { dicts: Vec<DictConfig> },
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Json(serde_json::Error),
+    Format(PathBuf, FormatError),
+    /// The dictionary type is not supported, such as the legacy `ocd` format.
+    UnsupportedDictionary(PathBuf),
+    /// A line of a dictionary in the text format is invalid.
+    Text(TextError),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            ConfigError::Json(err) => write!(f, "invalid configuration: {err}"),
+            ConfigError::Format(path, err) => {
+                write!(f, "invalid dictionary {}: {err}", path.display())
+            }
+            ConfigError::UnsupportedDictionary(path) => {
+                write!(f, "unsupported dictionary {}", path.display())
+            }
+            ConfigError::Text(err) => write!(f, "invalid dictionary {err}"),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(_, err) => Some(err),
+            ConfigError::Json(err) => Some(err),
+            ConfigError::Format(_, err) => Some(err),
+            ConfigError::UnsupportedDictionary(_) => None,
+            ConfigError::Text(err) => Some(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(err: serde_json::Error) -> Self {
+        ConfigError::Json(err)
+    }
+}
+
+impl From<TextError> for ConfigError {
+    fn from(err: TextError) -> Self {
+        ConfigError::Text(err)
+    }
+}
+
+fn read(path: &Path) -> Result<String, ConfigError> {
+    fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))
+}
+
+impl DictConfig {
+    fn load(&self, dir: &Path) -> Result<Box<dyn Matcher>, ConfigError> {
+        let trie: Trie<String> = match self {
+            DictConfig::Text { file } => {
+                let path = dir.join(file);
+                let text = read(&path)?;
+                let dictionary = TextLoader::new()
+                    .file(path.display().to_string())
+                    .parse(&text)?;
+
+                dictionary
+                    .into_iter()
+                    .map(|(key, values)| (key, values[0].to_string()))
+                    .collect()
+            }
+            DictConfig::Ocd2 { file } => {
+                let path = dir.join(file);
+                let bytes = fs::read(&path).map_err(|err| ConfigError::Io(path.clone(), err))?;
+
+                Trie::from_ocd2(&bytes).map_err(|err| ConfigError::Format(path, err))?
+            }
+            DictConfig::Ocd { file } => {
+                return Err(ConfigError::UnsupportedDictionary(dir.join(file)));
+            }
+            DictConfig::Group { dicts } => {
+                let dicts = dicts
+                    .iter()
+                    .map(|dict| dict.load(dir))
+                    .collect::<Result<_, _>>()?;
+                return Ok(Box::new(Group(dicts)));
+            }
+        };
+
+        Ok(Box::new(trie))
+    }
+}
+
+/// The dictionaries of an OpenCC group, which matches the input with its first dictionary that has
+/// a key the input starts with, even if a later one has a longer key.
+struct Group(Vec<Box<dyn Matcher>>);
+
+impl Group {
+    /// Whether a dictionary before the `i`th has `key`.
+    fn shadows(&self, i: usize, key: &str) -> bool {
+        self.0[..i].iter().any(|dict| {
+            dict.longest_match(key)
+                .is_some_and(|(_, len)| len == key.len())
+        })
+    }
+}
+
+impl Matcher for Group {
+    fn depth(&self) -> usize {
+        self.0.iter().map(|dict| dict.depth()).max().unwrap_or(0)
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        self.0.iter().find_map(|dict| dict.longest_match(s))
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        self.0.iter().find_map(|dict| dict.candidates(s))
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        Box::new(self.0.iter().enumerate().flat_map(move |(i, dict)| {
+            dict.entries().filter(move |(key, _)| !self.shadows(i, key))
+        }))
+    }
+}
+
+impl Converter {
+    /// Builds a converter from an OpenCC JSON configuration file.
+    ///
+    /// Dictionary files are resolved relative to the directory of the configuration. As in
+    /// OpenCC, a group matches the input with its first dictionary that has a key the input starts
+    /// with, so a later dictionary only provides matches where the earlier ones have none.
+    /// Dictionaries in the text format are validated by [`TextLoader`].
+    pub fn from_opencc_config(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let config: Config = serde_json::from_str(&read(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        let dictionaries = config
+            .conversion_chain
+            .iter()
+            .map(|conversion| conversion.dict.load(dir))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Converter::new(dictionaries))
+    }
+}
+
+#[cfg(all(test, feature = "st"))]
+mod tests {
+    use super::*;
+    use crate::Converters;
+
+    #[test]
+    fn test_from_opencc_config() {
+        let dir = std::env::temp_dir().join("hanconv-test-config");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Glossary.txt"), "云服务\t雲端服務\n").unwrap();
+        crate::write_ocd2(
+            [("鼠标", ["滑鼠"])],
+            fs::File::create(dir.join("Glossary.ocd2")).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("s2t.json"),
+            format!(
+                r#"{{
+                    "name": "Simplified Chinese to Traditional Chinese",
+                    "segmentation": {{ "type": "mmseg", "dict": {{ "type": "text", "file": "Glossary.txt" }} }},
+                    "conversion_chain": [{{
+                        "dict": {{
+                            "type": "group",
+                            "dicts": [
+                                {{ "type": "text", "file": "Glossary.txt" }},
+                                {{ "type": "ocd2", "file": "Glossary.ocd2" }},
+                                {{ "type": "text", "file": "{0}/data/STPhrases.txt" }},
+                                {{ "type": "text", "file": "{0}/data/STCharacters.txt" }}
+                            ]
+                        }}
+                    }}]
+                }}"#,
+                env!("CARGO_MANIFEST_DIR").replace('\\', "/")
+            ),
+        )
+        .unwrap();
+
+        let converter = Converter::from_opencc_config(dir.join("s2t.json")).unwrap();
+        assert_eq!(converter.convert("云服务器的鼠标"), "雲端服務器的滑鼠");
+        assert_eq!(
+            converter.convert("简繁转换"),
+            Converters::S2T.new_converter().convert("简繁转换")
+        );
+
+        // The first dictionary of a group that matches wins over a longer key of a later one.
+        fs::write(dir.join("Short.txt"), "一\t壹\n三\t叁\n").unwrap();
+        fs::write(dir.join("Long.txt"), "一二\t壹貳\n二三\t貳叁\n三\t參\n").unwrap();
+        fs::write(
+            dir.join("group.json"),
+            r#"{ "conversion_chain": [{ "dict": { "type": "group", "dicts": [
+                { "type": "text", "file": "Short.txt" },
+                { "type": "text", "file": "Long.txt" }
+            ] } }] }"#,
+        )
+        .unwrap();
+        let converter = Converter::from_opencc_config(dir.join("group.json")).unwrap();
+        assert_eq!(converter.convert("一二"), "壹二");
+        assert_eq!(converter.convert("二三"), "貳叁");
+        assert_eq!(converter.convert("三"), "叁");
+
+        assert!(matches!(
+            Converter::from_opencc_config(dir.join("missing.json")),
+            Err(ConfigError::Io(..))
+        ));
+
+        fs::write(dir.join("Invalid.txt"), "云服务\t雲端服務\n鼠标 滑鼠\n").unwrap();
+        fs::write(
+            dir.join("invalid.json"),
+            r#"{ "conversion_chain": [{ "dict": { "type": "text", "file": "Invalid.txt" } }] }"#,
+        )
+        .unwrap();
+        let Err(ConfigError::Text(err)) = Converter::from_opencc_config(dir.join("invalid.json"))
+        else {
+            panic!("expected an invalid text dictionary");
+        };
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(err.file.unwrap().ends_with("Invalid.txt"));
+    }
+}
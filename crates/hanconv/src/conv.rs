@@ -1,243 +1,1103 @@
-use crate::Dictionary::*;
-use crate::trie::Trie;
-use std::sync::LazyLock;
+#[cfg(all(
+    any(feature = "std", feature = "spin"),
+    any(
+        feature = "st",
+        feature = "ts",
+        feature = "tw",
+        feature = "hk",
+        feature = "jp"
+    )
+))]
+pub(crate) mod helpers;
+mod meta;
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
+mod tables;
 
+use crate::builder::ConverterBuilder;
+use crate::matcher::Matcher;
+use crate::protect::Protection;
+use crate::span::{self, Candidates, Segment, Span};
+use crate::stage::{Stage, write_converted};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
+use tables::*;
+
+#[cfg(feature = "std")]
+type Map<K, V> = ahash::AHashMap<K, V>;
+#[cfg(not(feature = "std"))]
+type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+#[cfg(feature = "std")]
+type Set<T> = ahash::AHashSet<T>;
+#[cfg(not(feature = "std"))]
+type Set<T> = alloc::collections::BTreeSet<T>;
+
+pub use meta::{BetweenError, Locale, ParseConvertersError};
+
+/// Preset conversions. A preset is available when the Cargo features of all the dictionary
+/// families it uses are enabled, e.g. `st` and `tw` for `S2TW`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Converters {
     /// Simplified Chinese to Traditional Chinese
     ///
     /// 简体中文 → 繁体中文
+    #[cfg(feature = "st")]
     S2T,
     /// Traditional Chinese to Simplified Chinese
     ///
     /// 繁体中文 → 简体中文
+    #[cfg(feature = "ts")]
     T2S,
     /// Simplified Chinese to Traditional Chinese (Taiwan)
     ///
     /// 简体中文 → 繁体中文（台湾）
+    #[cfg(all(feature = "st", feature = "tw"))]
     S2TW,
     /// Traditional Chinese (Taiwan) to Simplified Chinese
     ///
     /// 繁体中文（台湾）→ 简体中文
+    #[cfg(all(feature = "tw", feature = "ts"))]
     TW2S,
     /// Simplified Chinese to Traditional Chinese (Taiwan) with Taiwanese idiom
     ///
     /// 简体中文 → 繁体中文（台湾），转换为台湾常用词
+    #[cfg(all(feature = "st", feature = "tw"))]
     S2TWP,
     /// Traditional Chinese (Taiwan) to Simplified Chinese with Mainland Chinese idiom
     ///
     /// 繁体中文（台湾）→ 简体中文，转换为中国大陆常用词
+    #[cfg(all(feature = "tw", feature = "ts"))]
     TW2SP,
     /// Traditional Chinese to Traditional Chinese (Taiwan)
     ///
     /// 繁体中文 → 繁体中文（台湾）
+    #[cfg(feature = "tw")]
     T2TW,
     /// Traditional Chinese (Taiwan) to Traditional Chinese
     ///
     /// 繁体中文（台湾）→ 繁体中文
+    #[cfg(feature = "tw")]
     TW2T,
     /// Simplified Chinese to Traditional Chinese (Hong Kong)
     ///
     /// 简体中文 → 繁体中文（香港）
+    #[cfg(all(feature = "st", feature = "hk"))]
     S2HK,
     /// Traditional Chinese (Hong Kong) to Simplified Chinese
     ///
     /// 繁体中文（香港）→ 简体中文
+    #[cfg(all(feature = "hk", feature = "ts"))]
     HK2S,
     /// Traditional Chinese to Traditional Chinese (Hong Kong)
     ///
     /// 繁体中文 → 繁体中文（香港）
+    #[cfg(feature = "hk")]
     T2HK,
     /// Traditional Chinese (Hong Kong) to Traditional Chinese
     ///
     /// 繁体中文（香港）→ 繁体中文
+    #[cfg(feature = "hk")]
     HK2T,
     /// Traditional Chinese characters (Kyūjitai) to New Japanese Kanji (Shinjitai)
     ///
     /// 繁体字 → 日文新字体
+    #[cfg(feature = "jp")]
     T2JP,
     /// New Japanese Kanji (Shinjitai) to Traditional Chinese characters (Kyūjitai)
     ///
     /// 日文新字体 → 繁体字
+    #[cfg(feature = "jp")]
     JP2T,
+    /// Traditional Chinese (Hong Kong) to Traditional Chinese (Taiwan)
+    ///
+    /// 繁体中文（香港）→ 繁体中文（台湾）
+    #[cfg(all(feature = "hk", feature = "tw"))]
+    HK2TW,
+    /// Traditional Chinese (Taiwan) to Traditional Chinese (Hong Kong)
+    ///
+    /// 繁体中文（台湾）→ 繁体中文（香港）
+    #[cfg(all(feature = "tw", feature = "hk"))]
+    TW2HK,
+    /// Traditional Chinese (Hong Kong) to Traditional Chinese (Taiwan) with Taiwanese idiom
+    ///
+    /// 繁体中文（香港）→ 繁体中文（台湾），转换为台湾常用词
+    #[cfg(all(feature = "hk", feature = "tw"))]
+    HK2TWP,
+    /// Simplified Chinese to New Japanese Kanji (Shinjitai)
+    ///
+    /// 简体中文 → 日文新字体
+    #[cfg(all(feature = "st", feature = "jp"))]
+    S2JP,
+    /// New Japanese Kanji (Shinjitai) to Simplified Chinese
+    ///
+    /// 日文新字体 → 简体中文
+    #[cfg(all(feature = "jp", feature = "ts"))]
+    JP2S,
 }
 
-macro_rules! trie {
-    [$a:expr $(,$b:expr)*] => {
-        $a.iter()$(.chain($b.iter()))*.collect::<Trie<&'static str>>()
-    };
-}
-
-static S2T_DICT: LazyLock<Trie<&'static str>> = LazyLock::new(|| trie![STPhrases, STCharacters]);
-static T2S_DICT: LazyLock<Trie<&'static str>> = LazyLock::new(|| trie![TSPhrases, TSCharacters]);
-static T2TW_VARIANTS_DICT: LazyLock<Trie<&'static str>> = LazyLock::new(|| trie![TWVariants]);
-static T2TW_PHRASES_DICT: LazyLock<Trie<&'static str>> = LazyLock::new(|| trie![TWPhrases]);
-static TW2T_VARIANTS_DICT: LazyLock<Trie<&'static str>> =
-    LazyLock::new(|| trie![TWVariantsRevPhrases, TWVariantsRev]);
-static TW2T_PHRASES_VARIANTS_DICT: LazyLock<Trie<&'static str>> =
-    LazyLock::new(|| trie![TWPhrasesRev, TWVariantsRevPhrases, TWVariantsRev]);
-static T2HK_VARIANTS_DICT: LazyLock<Trie<&'static str>> = LazyLock::new(|| trie![HKVariants]);
-static HK2T_VARIANTS_DICT: LazyLock<Trie<&'static str>> =
-    LazyLock::new(|| trie![HKVariantsRevPhrases, HKVariantsRev]);
-static T2JP_VARIANTS_DICT: LazyLock<Trie<&'static str>> = LazyLock::new(|| trie![JPVariants]);
-static JP2T_DICT: LazyLock<Trie<&'static str>> =
-    LazyLock::new(|| trie![JPShinjitaiPhrases, JPShinjitaiCharacters, JPVariantsRev]);
-
 impl Converters {
     pub fn new_converter(&self) -> Converter {
-        Converter::new(self.dictionaries())
-    }
-
-    pub fn dictionaries(&self) -> Vec<&'static Trie<&'static str>> {
-        match self {
-            Converters::S2T => vec![&*S2T_DICT],
-            Converters::S2TW => vec![&*S2T_DICT, &*T2TW_VARIANTS_DICT],
-            Converters::S2TWP => vec![&*S2T_DICT, &*T2TW_PHRASES_DICT, &*T2TW_VARIANTS_DICT],
-            Converters::T2S => vec![&*T2S_DICT],
-            Converters::T2TW => vec![&*T2TW_VARIANTS_DICT],
-            Converters::TW2S => vec![&*TW2T_VARIANTS_DICT, &*T2S_DICT],
-            Converters::TW2SP => vec![&*TW2T_PHRASES_VARIANTS_DICT, &*T2S_DICT],
-            Converters::TW2T => vec![&*TW2T_VARIANTS_DICT],
-            Converters::S2HK => vec![&*S2T_DICT, &*T2HK_VARIANTS_DICT],
-            Converters::HK2S => vec![&*HK2T_VARIANTS_DICT, &*T2S_DICT],
-            Converters::HK2T => vec![&*HK2T_VARIANTS_DICT],
-            Converters::T2HK => vec![&*T2HK_VARIANTS_DICT],
-            Converters::T2JP => vec![&*T2JP_VARIANTS_DICT],
-            Converters::JP2T => vec![&*JP2T_DICT],
+        self.builder().build()
+    }
+
+    /// Returns a builder starting from the stages of this preset.
+    pub fn builder(&self) -> ConverterBuilder {
+        ConverterBuilder::new().preset(*self)
+    }
+
+    pub fn dictionaries(&self) -> Vec<&'static dyn Matcher> {
+        self.stages()
+            .into_iter()
+            .map(|(_, dictionary)| dictionary)
+            .collect()
+    }
+
+    /// Returns the stages of this preset in order, each named after its compiled dictionary, e.g.
+    /// `S2T`, `T2TW_PHRASES` and `T2TW_VARIANTS` for `S2TWP`.
+    pub fn stages(&self) -> Vec<(&'static str, &'static dyn Matcher)> {
+        match *self {
+            #[cfg(feature = "st")]
+            Converters::S2T => vec![("S2T", &S2T_DICT)],
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TW => vec![("S2T", &S2T_DICT), ("T2TW_VARIANTS", &T2TW_VARIANTS_DICT)],
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TWP => vec![
+                ("S2T", &S2T_DICT),
+                ("T2TW_PHRASES", &T2TW_PHRASES_DICT),
+                ("T2TW_VARIANTS", &T2TW_VARIANTS_DICT),
+            ],
+            #[cfg(feature = "ts")]
+            Converters::T2S => vec![("T2S", &T2S_DICT)],
+            #[cfg(feature = "tw")]
+            Converters::T2TW => vec![("T2TW_VARIANTS", &T2TW_VARIANTS_DICT)],
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2S => vec![("TW2T_VARIANTS", &TW2T_VARIANTS_DICT), ("T2S", &T2S_DICT)],
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2SP => vec![
+                ("TW2T_PHRASES_VARIANTS", &TW2T_PHRASES_VARIANTS_DICT),
+                ("T2S", &T2S_DICT),
+            ],
+            #[cfg(feature = "tw")]
+            Converters::TW2T => vec![("TW2T_VARIANTS", &TW2T_VARIANTS_DICT)],
+            #[cfg(all(feature = "st", feature = "hk"))]
+            Converters::S2HK => vec![("S2T", &S2T_DICT), ("T2HK_VARIANTS", &T2HK_VARIANTS_DICT)],
+            #[cfg(all(feature = "hk", feature = "ts"))]
+            Converters::HK2S => vec![("HK2T_VARIANTS", &HK2T_VARIANTS_DICT), ("T2S", &T2S_DICT)],
+            #[cfg(feature = "hk")]
+            Converters::HK2T => vec![("HK2T_VARIANTS", &HK2T_VARIANTS_DICT)],
+            #[cfg(feature = "hk")]
+            Converters::T2HK => vec![("T2HK_VARIANTS", &T2HK_VARIANTS_DICT)],
+            #[cfg(feature = "jp")]
+            Converters::T2JP => vec![("T2JP_VARIANTS", &T2JP_VARIANTS_DICT)],
+            #[cfg(feature = "jp")]
+            Converters::JP2T => vec![("JP2T", &JP2T_DICT)],
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TW => vec![
+                ("HK2T_VARIANTS", &HK2T_VARIANTS_DICT),
+                ("T2TW_VARIANTS", &T2TW_VARIANTS_DICT),
+            ],
+            #[cfg(all(feature = "tw", feature = "hk"))]
+            Converters::TW2HK => vec![
+                ("TW2T_VARIANTS", &TW2T_VARIANTS_DICT),
+                ("T2HK_VARIANTS", &T2HK_VARIANTS_DICT),
+            ],
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TWP => vec![
+                ("HK2T_VARIANTS", &HK2T_VARIANTS_DICT),
+                ("T2TW_PHRASES", &T2TW_PHRASES_DICT),
+                ("T2TW_VARIANTS", &T2TW_VARIANTS_DICT),
+            ],
+            #[cfg(all(feature = "st", feature = "jp"))]
+            Converters::S2JP => vec![("S2T", &S2T_DICT), ("T2JP_VARIANTS", &T2JP_VARIANTS_DICT)],
+            #[cfg(all(feature = "jp", feature = "ts"))]
+            Converters::JP2S => vec![("JP2T", &JP2T_DICT), ("T2S", &T2S_DICT)],
         }
     }
 }
 
-pub struct Converter(Vec<&'static Trie<&'static str>>);
+/// The name of a stage that does not come from a preset.
+pub(crate) const CUSTOM_STAGE: &str = "custom";
+
+/// Converts text by a pipeline of stages, each converting the output of the one before.
+///
+/// A converter owns its stages and can be shared across threads.
+pub struct Converter {
+    pub(crate) stages: Vec<Box<dyn Stage>>,
+    /// The name of each stage, listed by [`stages`](Converter::stages).
+    pub(crate) names: Vec<Cow<'static, str>>,
+    pub(crate) protection: Protection,
+}
 
 impl Converter {
-    pub fn new(dictionaries: Vec<&'static Trie<&'static str>>) -> Self {
-        Self(dictionaries)
+    /// Creates a converter with the given stages, each named `custom`, e.g. owned or
+    /// `Arc`-shared dictionaries.
+    pub fn new<S: Stage + 'static>(stages: impl IntoIterator<Item = S>) -> Self {
+        let stages: Vec<Box<dyn Stage>> = stages
+            .into_iter()
+            .map(|stage| Box::new(stage) as Box<dyn Stage>)
+            .collect();
+
+        Converter {
+            names: vec![Cow::Borrowed(CUSTOM_STAGE); stages.len()],
+            stages,
+            protection: Protection::new(),
+        }
     }
 
-    pub fn convert(&self, s: impl AsRef<str>) -> String {
-        match self.0.split_first() {
-            None => s.as_ref().to_string(),
-            Some((first, rest)) => {
-                let mut s = first.convert(s);
+    pub(crate) fn from_stages(stages: Vec<(Cow<'static, str>, Box<dyn Stage>)>) -> Self {
+        let (names, stages) = stages.into_iter().unzip();
 
-                for trie in rest {
-                    s = trie.convert(&s);
+        Converter {
+            stages,
+            names,
+            protection: Protection::new(),
+        }
+    }
+
+    /// Returns the names of the stages in order.
+    ///
+    /// Stages from presets are named after their compiled dictionaries, as listed by
+    /// [`Converters::stages`], and other stages are named `custom`. A stage made of several
+    /// dictionaries, by [`fuse`](Converter::fuse) or [`ConverterBuilder::overlay`], joins their
+    /// names with `+`.
+    pub fn stages(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_ref())
+    }
+
+    /// Chains `other` after this converter, so that its stages convert the output of this one.
+    ///
+    /// The protection of this converter is kept, or that of `other` if this one protects nothing.
+    pub fn then(mut self, other: Converter) -> Converter {
+        self.stages.extend(other.stages);
+        self.names.extend(other.names);
+        if self.protection.is_empty() {
+            self.protection = other.protection;
+        }
+        self
+    }
+
+    /// Copies the regions of the input found by `protection` verbatim.
+    pub fn protect(mut self, protection: Protection) -> Self {
+        self.protection = protection;
+        self
+    }
+
+    /// Fuses consecutive dictionaries into single-pass stages, without changing the output.
+    ///
+    /// Dictionaries whose keys are all single chars, such as the variant dictionaries of presets,
+    /// are first folded into the dictionary before them: it matches its own keys, with their values
+    /// converted by the single-char dictionary, and the single-char dictionary where it matches
+    /// nothing. Only the values that the conversion changes are copied, rather than every entry of
+    /// the dictionary before it.
+    ///
+    /// Each remaining pair of consecutive dictionaries, such as the phrase dictionaries of `S2TWP`,
+    /// is then composed: the values of the first are converted by the second once, and a match of
+    /// the first is replaced by its converted value wherever the second cannot match past its end.
+    /// Elsewhere, the fused stage converts by both dictionaries in turn until a match of the second
+    /// ends with a match of the first again. Every preset fuses into a single stage.
+    ///
+    /// A composed stage is not a dictionary, so it is opaque to
+    /// [`convert_with_spans`](Converter::convert_with_spans) and streaming. Stages other than
+    /// dictionaries are kept as they are.
+    pub fn fuse(self) -> Converter {
+        let mut folded: Vec<Box<dyn Stage>> = Vec::with_capacity(self.stages.len());
+        let mut folded_names: Vec<Cow<'static, str>> = Vec::with_capacity(self.names.len());
+
+        for (stage, name) in self.stages.into_iter().zip(self.names) {
+            let Some(dictionary) = stage.as_matcher() else {
+                folded.push(stage);
+                folded_names.push(name);
+                continue;
+            };
+
+            if dictionary.depth() == 0 {
+                continue;
+            }
+
+            let changed = match folded.last().and_then(|last| last.as_matcher()) {
+                Some(last) if dictionary.depth() == 1 => Some(
+                    last.entries()
+                        .filter_map(|(key, value)| match Stage::apply(&dictionary, value) {
+                            Cow::Owned(converted) if converted != value => Some((key, converted)),
+                            _ => None,
+                        })
+                        .collect::<Map<_, _>>(),
+                ),
+                _ => None,
+            };
+
+            match changed {
+                Some(changed) => {
+                    let first = folded.pop().unwrap();
+                    folded.push(Box::new(Folded {
+                        first,
+                        second: stage,
+                        changed,
+                    }));
+
+                    if let Some(last) = folded_names.last_mut() {
+                        *last = Cow::Owned(format!("{last}+{name}"));
+                    }
                 }
+                None => {
+                    folded.push(stage);
+                    folded_names.push(name);
+                }
+            }
+        }
+
+        let mut stages: Vec<Box<dyn Stage>> = Vec::with_capacity(folded.len());
+        let mut names: Vec<Cow<'static, str>> = Vec::with_capacity(folded_names.len());
+
+        for (stage, name) in folded.into_iter().zip(folded_names) {
+            match stages.last() {
+                Some(last) if last.as_matcher().is_some() && stage.as_matcher().is_some() => {
+                    let first = stages.pop().unwrap();
+                    stages.push(Box::new(Composed::new(first, stage)));
 
-                s
+                    if let Some(last) = names.last_mut() {
+                        *last = Cow::Owned(format!("{last}+{name}"));
+                    }
+                }
+                _ => {
+                    stages.push(stage);
+                    names.push(name);
+                }
             }
         }
+
+        Converter {
+            stages,
+            names,
+            protection: self.protection,
+        }
     }
-}
 
-/// Simplified Chinese to Traditional Chinese
-///
-/// 简体中文 → 繁体中文
-pub fn s2t(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::S2T.new_converter());
-    CONVERTER.convert(s)
-}
+    pub fn convert(&self, s: impl AsRef<str>) -> String {
+        let mut output = String::new();
+        self.convert_into(s, &mut output);
+        output
+    }
 
-/// Traditional Chinese to Simplified Chinese
-///
-/// 繁体中文 → 简体中文
-pub fn t2s(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::T2S.new_converter());
-    CONVERTER.convert(s)
-}
+    /// Appends the conversion of `s` to `output`.
+    ///
+    /// Only stages before the last one that change something allocate intermediate output.
+    pub fn convert_into(&self, s: impl AsRef<str>, output: &mut String) {
+        let s = s.as_ref();
+        output.reserve(s.len());
 
-/// Simplified Chinese to Traditional Chinese (Taiwan)
-///
-/// 简体中文 → 繁体中文（台湾）
-pub fn s2tw(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::S2TW.new_converter());
-    CONVERTER.convert(s)
-}
+        let Ok(()) = self.write(s, |piece| {
+            output.push_str(piece);
+            Ok::<_, Infallible>(())
+        });
+    }
 
-/// Traditional Chinese (Taiwan) to Simplified Chinese
-///
-/// 繁体中文（台湾）→ 简体中文
-pub fn tw2s(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::TW2S.new_converter());
-    CONVERTER.convert(s)
-}
+    /// Writes the conversion of `s` to `writer`.
+    pub fn convert_to_fmt(&self, s: impl AsRef<str>, writer: &mut impl fmt::Write) -> fmt::Result {
+        self.write(s.as_ref(), |piece| writer.write_str(piece))
+    }
 
-/// Simplified Chinese to Traditional Chinese (Taiwan) with Taiwanese idiom
-///
-/// 简体中文 → 繁体中文（台湾），转换为台湾常用词
-pub fn s2twp(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::S2TWP.new_converter());
-    CONVERTER.convert(s)
-}
+    /// Writes the conversion of `s` to `writer` as UTF-8.
+    #[cfg(feature = "std")]
+    pub fn convert_to_io(&self, s: impl AsRef<str>, writer: &mut impl io::Write) -> io::Result<()> {
+        self.write(s.as_ref(), |piece| writer.write_all(piece.as_bytes()))
+    }
 
-/// Traditional Chinese (Taiwan) to Simplified Chinese with Mainland Chinese idiom
-///
-/// 繁体中文（台湾）→ 简体中文，转换为中国大陆常用词
-pub fn tw2sp(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::TW2SP.new_converter());
-    CONVERTER.convert(s)
+    /// Converts `s`, borrowing it if no stage changed anything.
+    pub fn convert_cow<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        if self.protection.is_empty() {
+            return convert_stages(&self.stages, s);
+        }
+
+        let runs = self
+            .protection
+            .split(s)
+            .into_iter()
+            .map(|(range, protected)| match protected {
+                true => Cow::Borrowed(&s[range]),
+                false => convert_stages(&self.stages, &s[range]),
+            })
+            .collect::<Vec<_>>();
+
+        if runs.iter().all(|run| matches!(run, Cow::Borrowed(_))) {
+            Cow::Borrowed(s)
+        } else {
+            Cow::Owned(runs.concat())
+        }
+    }
+
+    /// Writes the conversion of `s` in pieces.
+    fn write<E>(&self, s: &str, mut write: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+        if self.protection.is_empty() {
+            return self.write_run(s, &mut write);
+        }
+
+        for (range, protected) in self.protection.split(s) {
+            if protected {
+                write(&s[range])?;
+            } else {
+                self.write_run(&s[range], &mut write)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the conversion of text without protected regions, the last stage writing its output
+    /// directly if it is a dictionary.
+    fn write_run<E>(
+        &self,
+        s: &str,
+        write: &mut impl FnMut(&str) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let Some((last, rest)) = self.stages.split_last() else {
+            return write(s);
+        };
+
+        let s = convert_stages(rest, s);
+        match last.as_matcher() {
+            Some(dictionary) => write_converted(dictionary, &s, write),
+            None => write(&last.apply(&s)),
+        }
+    }
+
+    /// Converts `s` and maps every byte range of `s` to the byte range of the output it produced.
+    ///
+    /// The spans are ordered and cover both `s` and the output without gaps. Runs of text left
+    /// unchanged share one span, and spans of consecutive stages are merged where a match of a
+    /// later stage covers parts of several matches of an earlier one.
+    pub fn convert_with_spans(&self, s: impl AsRef<str>) -> (String, Vec<Span>) {
+        let input = s.as_ref();
+        let mut output = String::with_capacity(input.len());
+        let mut spans = Vec::new();
+
+        for (range, protected) in self.protection.split(input) {
+            let start = output.len();
+
+            if protected {
+                output.push_str(&input[range.clone()]);
+                spans.push(Span {
+                    source: range,
+                    target: start..output.len(),
+                });
+            } else {
+                let (converted, run) = self.convert_run_with_spans(&input[range.clone()]);
+                output.push_str(&converted);
+                spans.extend(run.into_iter().map(|span| span.shift(range.start, start)));
+            }
+        }
+
+        let spans = span::merge_unchanged(spans, input, &output);
+        (output, spans)
+    }
+
+    fn convert_run_with_spans(&self, input: &str) -> (String, Vec<Span>) {
+        let mut output = input.to_string();
+        let mut spans = input
+            .char_indices()
+            .map(|(i, c)| Span {
+                source: i..i + c.len_utf8(),
+                target: i..i + c.len_utf8(),
+            })
+            .collect::<Vec<_>>();
+
+        for stage in &self.stages {
+            let (converted, stage) = match stage.as_matcher() {
+                Some(dictionary) => span::convert(dictionary, &output),
+                None => span::convert_opaque(stage.as_ref(), &output),
+            };
+            spans = span::compose(&spans, &stage);
+            output = converted;
+        }
+
+        (output, spans)
+    }
+
+    /// Converts `s` and lists every match with the chosen value and its alternatives.
+    ///
+    /// Alternatives of an earlier stage are converted by the later ones. Where a match of a later
+    /// stage covers parts of several matches of an earlier one, only the chosen value is listed.
+    pub fn convert_with_candidates(&self, s: impl AsRef<str>) -> (String, Vec<Candidates>) {
+        let input = s.as_ref();
+        let mut output = String::with_capacity(input.len());
+        let mut candidates = Vec::new();
+
+        for (range, protected) in self.protection.split(input) {
+            let start = output.len();
+
+            if protected {
+                output.push_str(&input[range]);
+            } else {
+                let (converted, run) = self.convert_run_with_candidates(&input[range.clone()]);
+                output.push_str(&converted);
+                candidates.extend(run.into_iter().map(|candidates| Candidates {
+                    span: candidates.span.shift(range.start, start),
+                    values: candidates.values,
+                }));
+            }
+        }
+
+        (output, candidates)
+    }
+
+    /// Lists the chars of `s` that a char-level entry converted to one of several candidates
+    /// without a phrase entry resolving them, the chosen value first.
+    pub fn ambiguities(&self, s: impl AsRef<str>) -> Vec<Candidates> {
+        let input = s.as_ref();
+        let (_, candidates) = self.convert_with_candidates(input);
+
+        candidates
+            .into_iter()
+            .filter(|candidates| {
+                candidates.values.len() > 1
+                    && input[candidates.span.source.clone()]
+                        .chars()
+                        .nth(1)
+                        .is_none()
+            })
+            .collect()
+    }
+
+    fn convert_run_with_candidates(&self, input: &str) -> (String, Vec<Candidates>) {
+        let mut output = input.to_string();
+        let mut segments = input
+            .char_indices()
+            .map(|(i, c)| {
+                let span = Span {
+                    source: i..i + c.len_utf8(),
+                    target: i..i + c.len_utf8(),
+                };
+                (span, None)
+            })
+            .collect::<Vec<Segment<String>>>();
+
+        for current_stage in &self.stages {
+            let (converted, stage) = match current_stage.as_matcher() {
+                Some(dictionary) => span::convert_candidates(dictionary, &output),
+                None => {
+                    let (converted, spans) = span::convert_opaque(current_stage.as_ref(), &output);
+                    (
+                        converted,
+                        spans.into_iter().map(|span| (span, None)).collect(),
+                    )
+                }
+            };
+
+            let first = segments
+                .iter()
+                .map(|(span, _)| span.clone())
+                .collect::<Vec<_>>();
+            let second = stage
+                .iter()
+                .map(|(span, _)| span.clone())
+                .collect::<Vec<_>>();
+
+            segments = span::align(&first, &second)
+                .into_iter()
+                .map(|(a, b)| {
+                    let span = span::join(&first, &second, (a.clone(), b.clone()));
+                    let chosen = converted[span.target.clone()].to_string();
+
+                    let values = match (&segments[a.clone()], &stage[b.clone()]) {
+                        ([(_, None)], [(_, None)]) => None,
+                        ([(_, previous)], [(_, current)]) => {
+                            let mut values = vec![chosen];
+                            let alternatives = previous
+                                .iter()
+                                .flat_map(|values| &values[1..])
+                                .map(|value| current_stage.apply(value).into_owned())
+                                .chain(
+                                    current
+                                        .iter()
+                                        .flat_map(|values| &values[1..])
+                                        .map(|value| value.to_string()),
+                                );
+
+                            for value in alternatives {
+                                if !values.contains(&value) {
+                                    values.push(value);
+                                }
+                            }
+
+                            Some(values)
+                        }
+                        (previous, current)
+                            if previous.iter().any(|(_, values)| values.is_some())
+                                || current.iter().any(|(_, values)| values.is_some()) =>
+                        {
+                            Some(vec![chosen])
+                        }
+                        _ => None,
+                    };
+
+                    (span, values)
+                })
+                .collect();
+            output = converted;
+        }
+
+        let candidates = segments
+            .into_iter()
+            .filter_map(|(span, values)| {
+                Some(Candidates {
+                    span,
+                    values: values?,
+                })
+            })
+            .collect();
+
+        (output, candidates)
+    }
 }
 
-/// Traditional Chinese to Traditional Chinese (Taiwan)
-///
-/// 繁体中文 → 繁体中文（台湾）
-pub fn t2tw(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::T2TW.new_converter());
-    CONVERTER.convert(s)
+/// A dictionary followed by a dictionary whose keys are all single chars, matched in one pass.
+struct Folded {
+    /// A stage that is a dictionary.
+    first: Box<dyn Stage>,
+    /// A stage that is a dictionary whose keys are all single chars.
+    second: Box<dyn Stage>,
+    /// The values of `first` that `second` changes, converted by `second`.
+    changed: Map<String, String>,
 }
 
-/// Traditional Chinese (Taiwan) to Traditional Chinese
-///
-/// 繁体中文（台湾）→ 繁体中文
-pub fn tw2t(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::TW2T.new_converter());
-    CONVERTER.convert(s)
+impl Folded {
+    fn first(&self) -> &dyn Matcher {
+        self.first.as_matcher().unwrap()
+    }
+
+    fn second(&self) -> &dyn Matcher {
+        self.second.as_matcher().unwrap()
+    }
+
+    fn value<'a>(&'a self, key: &str, value: &'a str) -> &'a str {
+        self.changed.get(key).map_or(value, String::as_str)
+    }
 }
 
-/// Simplified Chinese to Traditional Chinese (Hong Kong)
-///
-/// 简体中文 → 繁体中文（香港）
-pub fn s2hk(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::S2HK.new_converter());
-    CONVERTER.convert(s)
+impl Matcher for Folded {
+    fn depth(&self) -> usize {
+        self.first().depth().max(1)
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        match self.first().longest_match(s) {
+            Some((value, len)) => Some((self.value(&s[..len], value), len)),
+            None => self.second().longest_match(s),
+        }
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        Box::new(
+            self.second()
+                .entries()
+                .filter(|(key, _)| {
+                    self.first()
+                        .longest_match(key)
+                        .is_none_or(|(_, len)| len != key.len())
+                })
+                .chain(self.first().entries().map(|(key, value)| {
+                    let value = self.value(&key, value);
+                    (key, value)
+                })),
+        )
+    }
 }
 
-/// Traditional Chinese (Hong Kong) to Simplified Chinese
-///
-/// 繁体中文（香港）→ 简体中文
-pub fn hk2s(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::HK2S.new_converter());
-    CONVERTER.convert(s)
+/// Two dictionaries matched in one pass, the second converting the output of the first.
+struct Composed {
+    /// A stage that is a dictionary.
+    first: Box<dyn Stage>,
+    /// A stage that is a dictionary.
+    second: Box<dyn Stage>,
+    /// The values of `first` that `second` changes or may match past, as their start converted by
+    /// `second` and the length of the rest, which more text may extend a match of `second` over.
+    composed: Map<String, (String, usize)>,
+    /// The chars that `second` changes or may match past, as for `composed`.
+    chars: Map<char, (String, usize)>,
+    /// The keys of `chars`, as a bitset.
+    starts: Vec<u64>,
+    /// The proper prefixes of the keys of `second`.
+    prefixes: Set<String>,
 }
 
-/// Traditional Chinese to Traditional Chinese (Hong Kong)
-///
-/// 繁体中文 → 繁体中文（香港）
-pub fn t2hk(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::T2HK.new_converter());
-    CONVERTER.convert(s)
+impl Composed {
+    fn new(first: Box<dyn Stage>, second: Box<dyn Stage>) -> Self {
+        let mut chars = Map::default();
+        let mut prefixes = Set::default();
+
+        for (key, value) in second.as_matcher().unwrap().entries() {
+            let c = key.chars().next().unwrap();
+
+            if c.len_utf8() < key.len() {
+                chars.insert(c, (String::new(), c.len_utf8()));
+                prefixes.extend(
+                    key.char_indices()
+                        .skip(1)
+                        .map(|(i, _)| key[..i].to_string()),
+                );
+            } else if key != value {
+                chars.entry(c).or_insert_with(|| (value.to_string(), 0));
+            }
+        }
+
+        let mut starts = vec![
+            0;
+            chars
+                .keys()
+                .map(|&c| c as usize / 64 + 1)
+                .max()
+                .unwrap_or(0)
+        ];
+        for &c in chars.keys() {
+            starts[c as usize / 64] |= 1 << (c as usize % 64);
+        }
+
+        let mut composed = Composed {
+            first,
+            second,
+            composed: Map::default(),
+            chars,
+            starts,
+            prefixes,
+        };
+
+        composed.composed = composed
+            .first()
+            .entries()
+            .filter_map(|(key, value)| {
+                let mut converted = String::new();
+                let consumed = composed.convert(value, &mut converted, usize::MAX, false);
+
+                match consumed == value.len() && converted == value {
+                    true => None,
+                    false => Some((key, (converted, value.len() - consumed))),
+                }
+            })
+            .collect();
+
+        composed
+    }
+
+    fn first(&self) -> &dyn Matcher {
+        self.first.as_matcher().unwrap()
+    }
+
+    fn second(&self) -> &dyn Matcher {
+        self.second.as_matcher().unwrap()
+    }
+
+    /// Returns whether `second` changes `c` or may match past it.
+    fn starts(&self, c: char) -> bool {
+        self.starts
+            .get(c as usize / 64)
+            .is_some_and(|bits| bits >> (c as usize % 64) & 1 == 1)
+    }
+
+    /// Converts the start of `s` by `second` until a match ends at or past `end`, or where more text
+    /// may extend a match unless `finish` is set, and returns the length converted.
+    fn convert(&self, s: &str, output: &mut String, end: usize, finish: bool) -> usize {
+        let mut consumed = 0;
+
+        while consumed < end
+            && let Some(c) = s[consumed..].chars().next()
+        {
+            let rest = &s[consumed..];
+            if !finish && self.prefixes.contains(rest) {
+                break;
+            }
+
+            match self.second().longest_match(rest) {
+                Some((value, len)) => {
+                    output.push_str(value);
+                    consumed += len;
+                }
+                None => {
+                    output.push(c);
+                    consumed += c.len_utf8();
+                }
+            }
+        }
+
+        consumed
+    }
+
+    /// Returns the start of the value of a match of `first`, or of an unmatched char, converted by
+    /// `second`, and the rest, which more text may extend a match of `second` over.
+    fn lookup<'a>(&'a self, key: &'a str, value: Option<&'a str>) -> (&'a str, &'a str) {
+        let (composed, value) = match value {
+            Some(value) if !value.chars().any(|c| self.starts(c)) => return (value, ""),
+            Some(value) => (self.composed.get(key), value),
+            None => (self.chars.get(&key.chars().next().unwrap()), key),
+        };
+
+        match composed {
+            Some((converted, rest)) => (converted, &value[value.len() - rest..]),
+            None => (value, ""),
+        }
+    }
 }
 
-/// Traditional Chinese (Hong Kong) to Traditional Chinese
-///
-/// 繁体中文（香港）→ 繁体中文
-pub fn hk2t(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::HK2T.new_converter());
-    CONVERTER.convert(s)
+impl Stage for Composed {
+    fn apply<'s>(&self, input: &'s str) -> Cow<'s, str> {
+        let first = self.first();
+        let mut output = String::with_capacity(input.len());
+        // The output of `first` that `second` has not converted yet.
+        let mut pending = String::new();
+        // The start of the text copied as it is.
+        let mut start = 0;
+        let mut i = 0;
+
+        while let Some(c) = input[i..].chars().next() {
+            let (value, len) = match first.longest_match(&input[i..]) {
+                Some((value, len)) => (Some(value), len),
+                None => (None, c.len_utf8()),
+            };
+            let key = &input[i..i + len];
+
+            if pending.is_empty() {
+                if value.is_none() && !self.starts(c) {
+                    i += len;
+                    continue;
+                }
+
+                output.push_str(&input[start..i]);
+            }
+
+            i += len;
+            start = i;
+
+            if !pending.is_empty() {
+                let end = pending.len();
+                pending.push_str(value.unwrap_or(key));
+
+                // Unless a match crosses into the value, it is converted as if nothing preceded it.
+                let consumed = self.convert(&pending, &mut output, end, false);
+                if consumed != end {
+                    let consumed = consumed
+                        + self.convert(&pending[consumed..], &mut output, usize::MAX, false);
+                    pending.drain(..consumed);
+                    continue;
+                }
+
+                pending.clear();
+            }
+
+            let (converted, rest) = self.lookup(key, value);
+            output.push_str(converted);
+            pending.push_str(rest);
+        }
+
+        if start == 0 {
+            return Cow::Borrowed(input);
+        }
+
+        output.push_str(&input[start..]);
+        self.convert(&pending, &mut output, usize::MAX, true);
+
+        match output == input {
+            true => Cow::Borrowed(input),
+            false => Cow::Owned(output),
+        }
+    }
 }
 
-/// Traditional Chinese characters (Kyūjitai) to New Japanese Kanji (Shinjitai)
-///
-/// 繁体字 → 日文新字体
-pub fn t2jp(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::T2JP.new_converter());
-    CONVERTER.convert(s)
+/// Converts `s` by every stage in turn, borrowing it until a stage changes something.
+fn convert_stages<'s>(stages: &[Box<dyn Stage>], s: &'s str) -> Cow<'s, str> {
+    stages
+        .iter()
+        .fold(Cow::Borrowed(s), |s, stage| match stage.apply(&s) {
+            Cow::Borrowed(_) => s,
+            Cow::Owned(output) => Cow::Owned(output),
+        })
 }
 
-/// New Japanese Kanji (Shinjitai) to Traditional Chinese characters (Kyūjitai)
-///
-/// 日文新字体 → 繁体字
-pub fn jp2t(s: impl AsRef<str>) -> String {
-    static CONVERTER: LazyLock<Converter> = LazyLock::new(|| Converters::JP2T.new_converter());
-    CONVERTER.convert(s)
+#[cfg(all(test, feature = "st", feature = "tw"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_with_spans() {
+        let input = "打开鼠标设置，点击确定";
+        let (output, spans) = Converters::S2TWP.new_converter().convert_with_spans(input);
+
+        assert_eq!(output, "開啟滑鼠設定，點選確定");
+        assert_eq!(spans.first().unwrap().source.start, 0);
+        assert_eq!(spans.last().unwrap().source.end, input.len());
+        assert_eq!(spans.last().unwrap().target.end, output.len());
+        assert!(
+            spans
+                .windows(2)
+                .all(|w| w[0].source.end == w[1].source.start
+                    && w[0].target.end == w[1].target.start)
+        );
+        assert!(spans.contains(&Span {
+            source: input.find("鼠标").unwrap()..input.find("设置").unwrap(),
+            target: output.find("滑鼠").unwrap()..output.find("設定").unwrap(),
+        }));
+    }
+
+    #[test]
+    fn test_convert_with_candidates() {
+        let (output, candidates) = Converters::S2TW
+            .new_converter()
+            .convert_with_candidates("头发发现");
+
+        assert_eq!(output, "頭髮發現");
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].values, ["頭髮"]);
+        assert_eq!(candidates[1].values, ["發現"]);
+
+        let (output, candidates) = Converters::S2T
+            .new_converter()
+            .convert_with_candidates("后发");
+
+        assert_eq!(output, "後發");
+        assert_eq!(candidates[0].values, ["後", "后"]);
+        assert_eq!(candidates[1].values, ["發", "髮"]);
+    }
+
+    #[test]
+    fn test_ambiguities() {
+        let input = "后来他只说了一面之词";
+        let ambiguities = Converters::S2TWP.new_converter().ambiguities(input);
+        let chars = ambiguities
+            .iter()
+            .map(|candidates| &input[candidates.span.source.clone()])
+            .collect::<Vec<_>>();
+
+        assert_eq!(chars, ["了"]);
+        assert_eq!(ambiguities[0].values, ["了", "瞭"]);
+    }
+
+    #[test]
+    fn test_then() {
+        let input = "头发里的线";
+        let converter = Converters::S2T
+            .new_converter()
+            .then(Converters::T2TW.new_converter());
+
+        assert_eq!(
+            converter.stages().collect::<Vec<_>>(),
+            ["S2T", "T2TW_VARIANTS"]
+        );
+        assert_eq!(
+            converter.convert(input),
+            Converters::S2TW.new_converter().convert(input)
+        );
+
+        let fused = converter.fuse();
+        assert_eq!(fused.stages().collect::<Vec<_>>(), ["S2T+T2TW_VARIANTS"]);
+        assert_eq!(fused.convert(input), "頭髮裡的線");
+    }
+
+    #[test]
+    #[cfg(all(feature = "ts", feature = "hk", feature = "jp"))]
+    fn test_cross_region() {
+        let convert = |converters: Converters, s| converters.new_converter().convert(s);
+
+        assert_eq!(convert(Converters::HK2TW, "着裏的户口"), "著裡的戶口");
+        assert_eq!(convert(Converters::TW2HK, "著裡的戶口"), "着裏的户口");
+        assert_eq!(convert(Converters::HK2TW, "台灣的軟件"), "臺灣的軟件");
+        assert_eq!(convert(Converters::HK2TWP, "台灣的軟件"), "臺灣的軟體");
+        assert_eq!(convert(Converters::S2JP, "学习国语"), "学習国語");
+        assert_eq!(convert(Converters::JP2S, "国語の学習"), "国语の学习");
+    }
+
+    #[test]
+    fn test_convert_cow() {
+        let converter = Converters::S2TWP.new_converter();
+
+        assert!(matches!(
+            converter.convert_cow("Hello"),
+            Cow::Borrowed("Hello")
+        ));
+        assert_eq!(converter.convert_cow("打开鼠标"), "開啟滑鼠");
+
+        let mut output = String::from("> ");
+        converter.convert_into("打开鼠标", &mut output);
+        assert_eq!(output, "> 開啟滑鼠");
+
+        #[cfg(feature = "std")]
+        {
+            let mut output = Vec::new();
+            converter.convert_to_io("打开鼠标", &mut output).unwrap();
+            assert_eq!(output, "開啟滑鼠".as_bytes());
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "ts", feature = "hk", feature = "jp"))]
+    fn test_fuse() {
+        use crate::Dictionary;
+        use crate::trie::Trie;
+
+        let words = Dictionary::STPhrases
+            .iter()
+            .step_by(16)
+            .chain(
+                [
+                    Dictionary::TSPhrases,
+                    Dictionary::TWPhrases,
+                    Dictionary::TWPhrasesRev,
+                    Dictionary::TWVariantsRevPhrases,
+                    Dictionary::HKVariantsRevPhrases,
+                    Dictionary::JPShinjitaiPhrases,
+                ]
+                .iter()
+                .flat_map(|dictionary| dictionary.iter()),
+            )
+            .flat_map(|(key, value)| [key, value])
+            .collect::<Vec<_>>();
+        let text = words.concat() + &words.join("，");
+
+        for &converters in Converters::ALL {
+            let converter = converters.new_converter();
+            let fused = converters.new_converter().fuse();
+
+            assert_eq!(fused.stages.len(), 1);
+            assert_eq!(fused.convert(&text), converter.convert(&text));
+        }
+
+        // The phrases of the second dictionary that span matches of the first, or that start in
+        // one and end in more text, are converted as in sequence.
+        let dictionaries = || {
+            [
+                Trie::from_iter([("甲乙", "AB"), ("丙", "C")]),
+                Trie::from_iter([("BC", "x"), ("ABCD", "y"), ("A", "a")]),
+            ]
+        };
+        let converter = Converter::new(dictionaries());
+        let fused = Converter::new(dictionaries()).fuse();
+        for input in [
+            "甲乙",
+            "甲乙丙",
+            "甲乙丙D",
+            "甲乙丙DE",
+            "丙甲乙丙",
+            "ABC甲乙",
+        ] {
+            assert_eq!(fused.convert(input), converter.convert(input), "{input}");
+        }
+    }
 }
@@ -0,0 +1,177 @@
+use super::{Converter, Converters};
+use alloc::string::String;
+#[cfg(all(not(feature = "std"), feature = "spin"))]
+use spin::Lazy;
+#[cfg(feature = "std")]
+use std::sync::LazyLock as Lazy;
+
+/// Simplified Chinese to Traditional Chinese
+///
+/// 简体中文 → 繁体中文
+#[cfg(feature = "st")]
+pub fn s2t(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::S2T.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese to Simplified Chinese
+///
+/// 繁体中文 → 简体中文
+#[cfg(feature = "ts")]
+pub fn t2s(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::T2S.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Simplified Chinese to Traditional Chinese (Taiwan)
+///
+/// 简体中文 → 繁体中文（台湾）
+#[cfg(all(feature = "st", feature = "tw"))]
+pub fn s2tw(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::S2TW.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Taiwan) to Simplified Chinese
+///
+/// 繁体中文（台湾）→ 简体中文
+#[cfg(all(feature = "tw", feature = "ts"))]
+pub fn tw2s(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::TW2S.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Simplified Chinese to Traditional Chinese (Taiwan) with Taiwanese idiom
+///
+/// 简体中文 → 繁体中文（台湾），转换为台湾常用词
+#[cfg(all(feature = "st", feature = "tw"))]
+pub fn s2twp(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::S2TWP.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Taiwan) to Simplified Chinese with Mainland Chinese idiom
+///
+/// 繁体中文（台湾）→ 简体中文，转换为中国大陆常用词
+#[cfg(all(feature = "tw", feature = "ts"))]
+pub fn tw2sp(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::TW2SP.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese to Traditional Chinese (Taiwan)
+///
+/// 繁体中文 → 繁体中文（台湾）
+#[cfg(feature = "tw")]
+pub fn t2tw(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::T2TW.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Taiwan) to Traditional Chinese
+///
+/// 繁体中文（台湾）→ 繁体中文
+#[cfg(feature = "tw")]
+pub fn tw2t(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::TW2T.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Simplified Chinese to Traditional Chinese (Hong Kong)
+///
+/// 简体中文 → 繁体中文（香港）
+#[cfg(all(feature = "st", feature = "hk"))]
+pub fn s2hk(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::S2HK.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Hong Kong) to Simplified Chinese
+///
+/// 繁体中文（香港）→ 简体中文
+#[cfg(all(feature = "hk", feature = "ts"))]
+pub fn hk2s(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::HK2S.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese to Traditional Chinese (Hong Kong)
+///
+/// 繁体中文 → 繁体中文（香港）
+#[cfg(feature = "hk")]
+pub fn t2hk(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::T2HK.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Hong Kong) to Traditional Chinese
+///
+/// 繁体中文（香港）→ 繁体中文
+#[cfg(feature = "hk")]
+pub fn hk2t(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::HK2T.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese characters (Kyūjitai) to New Japanese Kanji (Shinjitai)
+///
+/// 繁体字 → 日文新字体
+#[cfg(feature = "jp")]
+pub fn t2jp(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::T2JP.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// New Japanese Kanji (Shinjitai) to Traditional Chinese characters (Kyūjitai)
+///
+/// 日文新字体 → 繁体字
+#[cfg(feature = "jp")]
+pub fn jp2t(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::JP2T.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Hong Kong) to Traditional Chinese (Taiwan)
+///
+/// 繁体中文（香港）→ 繁体中文（台湾）
+#[cfg(all(feature = "hk", feature = "tw"))]
+pub fn hk2tw(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::HK2TW.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Taiwan) to Traditional Chinese (Hong Kong)
+///
+/// 繁体中文（台湾）→ 繁体中文（香港）
+#[cfg(all(feature = "tw", feature = "hk"))]
+pub fn tw2hk(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::TW2HK.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Traditional Chinese (Hong Kong) to Traditional Chinese (Taiwan) with Taiwanese idiom
+///
+/// 繁体中文（香港）→ 繁体中文（台湾），转换为台湾常用词
+#[cfg(all(feature = "hk", feature = "tw"))]
+pub fn hk2twp(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::HK2TWP.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// Simplified Chinese to New Japanese Kanji (Shinjitai)
+///
+/// 简体中文 → 日文新字体
+#[cfg(all(feature = "st", feature = "jp"))]
+pub fn s2jp(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::S2JP.new_converter());
+    CONVERTER.convert(s)
+}
+
+/// New Japanese Kanji (Shinjitai) to Simplified Chinese
+///
+/// 日文新字体 → 简体中文
+#[cfg(all(feature = "jp", feature = "ts"))]
+pub fn jp2s(s: impl AsRef<str>) -> String {
+    static CONVERTER: Lazy<Converter> = Lazy::new(|| Converters::JP2S.new_converter());
+    CONVERTER.convert(s)
+}
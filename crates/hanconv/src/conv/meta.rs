@@ -0,0 +1,547 @@
+use super::Converters;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+
+/// The script and region of the text on one side of a preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Locale {
+    /// The BCP 47 language tag, e.g. `zh-Hant-TW`.
+    pub tag: &'static str,
+    /// The English name, e.g. `Traditional Chinese (Taiwan)`.
+    pub english: &'static str,
+    /// The Chinese name, e.g. `繁体中文（台湾）`.
+    pub chinese: &'static str,
+}
+
+impl Locale {
+    pub const SIMPLIFIED: Locale = Locale {
+        tag: "zh-Hans",
+        english: "Simplified Chinese",
+        chinese: "简体中文",
+    };
+    pub const TRADITIONAL: Locale = Locale {
+        tag: "zh-Hant",
+        english: "Traditional Chinese",
+        chinese: "繁体中文",
+    };
+    pub const TAIWAN: Locale = Locale {
+        tag: "zh-Hant-TW",
+        english: "Traditional Chinese (Taiwan)",
+        chinese: "繁体中文（台湾）",
+    };
+    pub const HONG_KONG: Locale = Locale {
+        tag: "zh-Hant-HK",
+        english: "Traditional Chinese (Hong Kong)",
+        chinese: "繁体中文（香港）",
+    };
+    pub const JAPANESE: Locale = Locale {
+        tag: "ja",
+        english: "New Japanese Kanji (Shinjitai)",
+        chinese: "日文新字体",
+    };
+}
+
+impl Locale {
+    /// Returns the locale of a BCP 47 language tag, e.g. `zh-CN`, `zh-Hans`, `zh-Hant-TW`, `zh-HK`
+    /// or `ja`, or `None` if the tag is not Japanese or Chinese with a known script or region.
+    ///
+    /// A script subtag takes precedence over the region, so `zh-Hant-CN` is Traditional Chinese.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next()?;
+
+        if language.eq_ignore_ascii_case("ja") {
+            return Some(Locale::JAPANESE);
+        }
+        if !language.eq_ignore_ascii_case("zh") {
+            return None;
+        }
+
+        let (mut script, mut region) = (None, None);
+        for subtag in subtags.take_while(|subtag| subtag.len() > 1) {
+            let digits = subtag.bytes().all(|b| b.is_ascii_digit());
+
+            match subtag.len() {
+                4 if script.is_none() && region.is_none() => script = Some(subtag),
+                2 if region.is_none() => region = Some(subtag),
+                3 if region.is_none() && digits => region = Some(subtag),
+                _ => {}
+            }
+        }
+
+        let is = |subtag: Option<&str>, names: &[&str]| {
+            subtag.is_some_and(|subtag| names.iter().any(|name| subtag.eq_ignore_ascii_case(name)))
+        };
+
+        if is(script, &["Hans"]) {
+            Some(Locale::SIMPLIFIED)
+        } else if is(region, &["TW"]) {
+            Some(Locale::TAIWAN)
+        } else if is(region, &["HK", "MO"]) {
+            Some(Locale::HONG_KONG)
+        } else if is(script, &["Hant"]) {
+            Some(Locale::TRADITIONAL)
+        } else if is(region, &["CN", "SG", "MY"]) {
+            Some(Locale::SIMPLIFIED)
+        } else {
+            None
+        }
+    }
+}
+
+/// An error choosing a preset by the locales of its input and output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BetweenError {
+    /// The tag is not a locale that [`Locale::from_tag`] recognizes.
+    UnknownLocale(String),
+    /// No enabled preset converts from the first locale to the second.
+    NoPreset(Locale, Locale),
+}
+
+impl Display for BetweenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BetweenError::UnknownLocale(tag) => write!(f, "unknown locale {tag}"),
+            BetweenError::NoPreset(from, to) => {
+                write!(f, "no conversion from {} to {}", from.tag, to.tag)
+            }
+        }
+    }
+}
+
+impl Error for BetweenError {}
+
+/// An error parsing the name of a preset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConvertersError;
+
+impl Display for ParseConvertersError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown conversion")
+    }
+}
+
+impl Error for ParseConvertersError {}
+
+impl Converters {
+    /// The presets enabled by the Cargo features, each followed by its reverse if it has one.
+    pub const ALL: &'static [Converters] = &[
+        #[cfg(feature = "st")]
+        Converters::S2T,
+        #[cfg(feature = "ts")]
+        Converters::T2S,
+        #[cfg(all(feature = "st", feature = "tw"))]
+        Converters::S2TW,
+        #[cfg(all(feature = "tw", feature = "ts"))]
+        Converters::TW2S,
+        #[cfg(all(feature = "st", feature = "tw"))]
+        Converters::S2TWP,
+        #[cfg(all(feature = "tw", feature = "ts"))]
+        Converters::TW2SP,
+        #[cfg(feature = "tw")]
+        Converters::T2TW,
+        #[cfg(feature = "tw")]
+        Converters::TW2T,
+        #[cfg(all(feature = "st", feature = "hk"))]
+        Converters::S2HK,
+        #[cfg(all(feature = "hk", feature = "ts"))]
+        Converters::HK2S,
+        #[cfg(feature = "hk")]
+        Converters::T2HK,
+        #[cfg(feature = "hk")]
+        Converters::HK2T,
+        #[cfg(feature = "jp")]
+        Converters::T2JP,
+        #[cfg(feature = "jp")]
+        Converters::JP2T,
+        #[cfg(all(feature = "hk", feature = "tw"))]
+        Converters::HK2TW,
+        #[cfg(all(feature = "tw", feature = "hk"))]
+        Converters::TW2HK,
+        #[cfg(all(feature = "hk", feature = "tw"))]
+        Converters::HK2TWP,
+        #[cfg(all(feature = "st", feature = "jp"))]
+        Converters::S2JP,
+        #[cfg(all(feature = "jp", feature = "ts"))]
+        Converters::JP2S,
+    ];
+
+    /// Returns the lowercase name of the preset, as used by OpenCC configurations, e.g. `s2twp`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "st")]
+            Converters::S2T => "s2t",
+            #[cfg(feature = "ts")]
+            Converters::T2S => "t2s",
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TW => "s2tw",
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2S => "tw2s",
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TWP => "s2twp",
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2SP => "tw2sp",
+            #[cfg(feature = "tw")]
+            Converters::T2TW => "t2tw",
+            #[cfg(feature = "tw")]
+            Converters::TW2T => "tw2t",
+            #[cfg(all(feature = "st", feature = "hk"))]
+            Converters::S2HK => "s2hk",
+            #[cfg(all(feature = "hk", feature = "ts"))]
+            Converters::HK2S => "hk2s",
+            #[cfg(feature = "hk")]
+            Converters::T2HK => "t2hk",
+            #[cfg(feature = "hk")]
+            Converters::HK2T => "hk2t",
+            #[cfg(feature = "jp")]
+            Converters::T2JP => "t2jp",
+            #[cfg(feature = "jp")]
+            Converters::JP2T => "jp2t",
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TW => "hk2tw",
+            #[cfg(all(feature = "tw", feature = "hk"))]
+            Converters::TW2HK => "tw2hk",
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TWP => "hk2twp",
+            #[cfg(all(feature = "st", feature = "jp"))]
+            Converters::S2JP => "s2jp",
+            #[cfg(all(feature = "jp", feature = "ts"))]
+            Converters::JP2S => "jp2s",
+        }
+    }
+
+    /// Returns the locales of the input and the output.
+    pub const fn locales(self) -> (Locale, Locale) {
+        match self {
+            #[cfg(feature = "st")]
+            Converters::S2T => (Locale::SIMPLIFIED, Locale::TRADITIONAL),
+            #[cfg(feature = "ts")]
+            Converters::T2S => (Locale::TRADITIONAL, Locale::SIMPLIFIED),
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TW | Converters::S2TWP => (Locale::SIMPLIFIED, Locale::TAIWAN),
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2S | Converters::TW2SP => (Locale::TAIWAN, Locale::SIMPLIFIED),
+            #[cfg(feature = "tw")]
+            Converters::T2TW => (Locale::TRADITIONAL, Locale::TAIWAN),
+            #[cfg(feature = "tw")]
+            Converters::TW2T => (Locale::TAIWAN, Locale::TRADITIONAL),
+            #[cfg(all(feature = "st", feature = "hk"))]
+            Converters::S2HK => (Locale::SIMPLIFIED, Locale::HONG_KONG),
+            #[cfg(all(feature = "hk", feature = "ts"))]
+            Converters::HK2S => (Locale::HONG_KONG, Locale::SIMPLIFIED),
+            #[cfg(feature = "hk")]
+            Converters::T2HK => (Locale::TRADITIONAL, Locale::HONG_KONG),
+            #[cfg(feature = "hk")]
+            Converters::HK2T => (Locale::HONG_KONG, Locale::TRADITIONAL),
+            #[cfg(feature = "jp")]
+            Converters::T2JP => (Locale::TRADITIONAL, Locale::JAPANESE),
+            #[cfg(feature = "jp")]
+            Converters::JP2T => (Locale::JAPANESE, Locale::TRADITIONAL),
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TW | Converters::HK2TWP => (Locale::HONG_KONG, Locale::TAIWAN),
+            #[cfg(all(feature = "tw", feature = "hk"))]
+            Converters::TW2HK => (Locale::TAIWAN, Locale::HONG_KONG),
+            #[cfg(all(feature = "st", feature = "jp"))]
+            Converters::S2JP => (Locale::SIMPLIFIED, Locale::JAPANESE),
+            #[cfg(all(feature = "jp", feature = "ts"))]
+            Converters::JP2S => (Locale::JAPANESE, Locale::SIMPLIFIED),
+        }
+    }
+
+    /// Returns the locale of the input.
+    pub const fn source(self) -> Locale {
+        self.locales().0
+    }
+
+    /// Returns the locale of the output.
+    pub const fn target(self) -> Locale {
+        self.locales().1
+    }
+
+    /// Returns the enabled preset converting back from the target to the source with the same
+    /// idiom, e.g. `TW2SP` for `S2TWP`, or `None` if there is none, as for `HK2TWP`.
+    pub fn reverse(self) -> Option<Converters> {
+        let (source, target) = self.locales();
+
+        Converters::ALL.iter().copied().find(|converters| {
+            converters.locales() == (target, source) && converters.idiom() == self.idiom()
+        })
+    }
+
+    /// Returns whether the preset also converts phrases to the idiom of the target region.
+    pub const fn idiom(self) -> bool {
+        match self {
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TWP => true,
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2SP => true,
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TWP => true,
+            // Unreachable without any dictionary family.
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Returns the English and Chinese descriptions of the preset.
+    pub const fn descriptions(self) -> (&'static str, &'static str) {
+        match self {
+            #[cfg(feature = "st")]
+            Converters::S2T => (
+                "Simplified Chinese to Traditional Chinese",
+                "简体中文 → 繁体中文",
+            ),
+            #[cfg(feature = "ts")]
+            Converters::T2S => (
+                "Traditional Chinese to Simplified Chinese",
+                "繁体中文 → 简体中文",
+            ),
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TW => (
+                "Simplified Chinese to Traditional Chinese (Taiwan)",
+                "简体中文 → 繁体中文（台湾）",
+            ),
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2S => (
+                "Traditional Chinese (Taiwan) to Simplified Chinese",
+                "繁体中文（台湾）→ 简体中文",
+            ),
+            #[cfg(all(feature = "st", feature = "tw"))]
+            Converters::S2TWP => (
+                "Simplified Chinese to Traditional Chinese (Taiwan) with Taiwanese idiom",
+                "简体中文 → 繁体中文（台湾），转换为台湾常用词",
+            ),
+            #[cfg(all(feature = "tw", feature = "ts"))]
+            Converters::TW2SP => (
+                "Traditional Chinese (Taiwan) to Simplified Chinese with Mainland Chinese idiom",
+                "繁体中文（台湾）→ 简体中文，转换为中国大陆常用词",
+            ),
+            #[cfg(feature = "tw")]
+            Converters::T2TW => (
+                "Traditional Chinese to Traditional Chinese (Taiwan)",
+                "繁体中文 → 繁体中文（台湾）",
+            ),
+            #[cfg(feature = "tw")]
+            Converters::TW2T => (
+                "Traditional Chinese (Taiwan) to Traditional Chinese",
+                "繁体中文（台湾）→ 繁体中文",
+            ),
+            #[cfg(all(feature = "st", feature = "hk"))]
+            Converters::S2HK => (
+                "Simplified Chinese to Traditional Chinese (Hong Kong)",
+                "简体中文 → 繁体中文（香港）",
+            ),
+            #[cfg(all(feature = "hk", feature = "ts"))]
+            Converters::HK2S => (
+                "Traditional Chinese (Hong Kong) to Simplified Chinese",
+                "繁体中文（香港）→ 简体中文",
+            ),
+            #[cfg(feature = "hk")]
+            Converters::T2HK => (
+                "Traditional Chinese to Traditional Chinese (Hong Kong)",
+                "繁体中文 → 繁体中文（香港）",
+            ),
+            #[cfg(feature = "hk")]
+            Converters::HK2T => (
+                "Traditional Chinese (Hong Kong) to Traditional Chinese",
+                "繁体中文（香港）→ 繁体中文",
+            ),
+            #[cfg(feature = "jp")]
+            Converters::T2JP => (
+                "Traditional Chinese characters (Kyūjitai) to New Japanese Kanji (Shinjitai)",
+                "繁体字 → 日文新字体",
+            ),
+            #[cfg(feature = "jp")]
+            Converters::JP2T => (
+                "New Japanese Kanji (Shinjitai) to Traditional Chinese characters (Kyūjitai)",
+                "日文新字体 → 繁体字",
+            ),
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TW => (
+                "Traditional Chinese (Hong Kong) to Traditional Chinese (Taiwan)",
+                "繁体中文（香港）→ 繁体中文（台湾）",
+            ),
+            #[cfg(all(feature = "tw", feature = "hk"))]
+            Converters::TW2HK => (
+                "Traditional Chinese (Taiwan) to Traditional Chinese (Hong Kong)",
+                "繁体中文（台湾）→ 繁体中文（香港）",
+            ),
+            #[cfg(all(feature = "hk", feature = "tw"))]
+            Converters::HK2TWP => (
+                "Traditional Chinese (Hong Kong) to Traditional Chinese (Taiwan) with Taiwanese idiom",
+                "繁体中文（香港）→ 繁体中文（台湾），转换为台湾常用词",
+            ),
+            #[cfg(all(feature = "st", feature = "jp"))]
+            Converters::S2JP => (
+                "Simplified Chinese to New Japanese Kanji (Shinjitai)",
+                "简体中文 → 日文新字体",
+            ),
+            #[cfg(all(feature = "jp", feature = "ts"))]
+            Converters::JP2S => (
+                "New Japanese Kanji (Shinjitai) to Simplified Chinese",
+                "日文新字体 → 简体中文",
+            ),
+        }
+    }
+
+    /// Returns the English description of the preset, e.g. `Simplified Chinese to Traditional
+    /// Chinese`.
+    pub const fn description(self) -> &'static str {
+        self.descriptions().0
+    }
+
+    /// Returns the Chinese description of the preset, e.g. `简体中文 → 繁体中文`.
+    pub const fn chinese_description(self) -> &'static str {
+        self.descriptions().1
+    }
+}
+
+impl Converters {
+    /// Returns the preset converting text of the locale `from` to the locale `to`, both BCP 47
+    /// tags, converting characters only, e.g. `S2TW` from `zh-Hans` to `zh-TW`.
+    pub fn between(from: &str, to: &str) -> Result<Converters, BetweenError> {
+        Converters::between_with_idiom(from, to, false)
+    }
+
+    /// Returns the preset converting text of the locale `from` to the locale `to` like
+    /// [`between`](Converters::between), preferring one that also converts phrases to the idiom of
+    /// the target region if `idiom` is true, e.g. `S2TWP` from `zh-Hans` to `zh-TW`.
+    pub fn between_with_idiom(
+        from: &str,
+        to: &str,
+        idiom: bool,
+    ) -> Result<Converters, BetweenError> {
+        let locale = |tag: &str| {
+            Locale::from_tag(tag).ok_or_else(|| BetweenError::UnknownLocale(tag.to_string()))
+        };
+        let (from, to) = (locale(from)?, locale(to)?);
+
+        let mut presets = Converters::ALL
+            .iter()
+            .copied()
+            .filter(|converters| converters.locales() == (from, to));
+
+        let preset = if idiom {
+            presets
+                .clone()
+                .find(|converters| converters.idiom())
+                .or(presets.next())
+        } else {
+            presets.find(|converters| !converters.idiom())
+        };
+
+        preset.ok_or(BetweenError::NoPreset(from, to))
+    }
+}
+
+impl Display for Converters {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Converters {
+    type Err = ParseConvertersError;
+
+    /// Parses the name of a preset, ignoring case, e.g. `s2twp` or `S2TWP`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Converters::ALL
+            .iter()
+            .copied()
+            .find(|converters| converters.name().eq_ignore_ascii_case(s))
+            .ok_or(ParseConvertersError)
+    }
+}
+
+/// Serializes as the name of the preset.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Converters {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserializes from the name of a preset, ignoring case.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Converters {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = alloc::string::String::deserialize(deserializer)?;
+
+        name.parse().map_err(|_| {
+            serde::de::Error::invalid_value(serde::de::Unexpected::Str(&name), &"a preset name")
+        })
+    }
+}
+
+#[cfg(all(test, feature = "st", feature = "tw"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for &converters in Converters::ALL {
+            assert_eq!(converters.to_string().parse(), Ok(converters));
+        }
+
+        assert_eq!("S2TWP".parse(), Ok(Converters::S2TWP));
+        assert_eq!("s2x".parse::<Converters>(), Err(ParseConvertersError));
+        assert_eq!(Converters::S2TWP.target(), Locale::TAIWAN);
+        assert!(Converters::S2TWP.idiom() && !Converters::S2TW.idiom());
+        assert_eq!(Converters::T2TW.reverse(), Some(Converters::TW2T));
+    }
+
+    #[test]
+    #[cfg(all(feature = "ts", feature = "hk", feature = "jp"))]
+    fn test_between() {
+        assert_eq!(
+            Converters::between("zh-Hans", "zh-TW"),
+            Ok(Converters::S2TW)
+        );
+        assert_eq!(
+            Converters::between_with_idiom("zh-CN", "zh-Hant-TW", true),
+            Ok(Converters::S2TWP)
+        );
+        assert_eq!(
+            Converters::between_with_idiom("zh-Hans", "zh-Hant", true),
+            Ok(Converters::S2T)
+        );
+        assert_eq!(
+            Converters::between("zh-HK", "zh-Hans"),
+            Ok(Converters::HK2S)
+        );
+        assert_eq!(Converters::between("zh_hant", "ja"), Ok(Converters::T2JP));
+        assert_eq!(Converters::between("zh-CN", "ja"), Ok(Converters::S2JP));
+        assert_eq!(
+            Converters::between_with_idiom("zh-HK", "zh-TW", true),
+            Ok(Converters::HK2TWP)
+        );
+        assert_eq!(Converters::between("zh-TW", "zh-MO"), Ok(Converters::TW2HK));
+        assert_eq!(
+            Converters::between("zh-TW", "zh-TW"),
+            Err(BetweenError::NoPreset(Locale::TAIWAN, Locale::TAIWAN))
+        );
+        assert_eq!(
+            Converters::between("zh", "zh-TW"),
+            Err(BetweenError::UnknownLocale("zh".into()))
+        );
+        assert_eq!(Converters::S2TWP.reverse(), Some(Converters::TW2SP));
+        assert_eq!(Converters::HK2TW.reverse(), Some(Converters::TW2HK));
+        assert_eq!(Converters::HK2TWP.reverse(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        assert_eq!(
+            serde_json::to_string(&Converters::S2TWP).unwrap(),
+            r#""s2twp""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Converters>(r#""TW2SP""#).unwrap(),
+            Converters::TW2SP
+        );
+        assert!(serde_json::from_str::<Converters>(r#""s2x""#).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::compact::CompactTrie;
+#[cfg(feature = "compress")]
+use alloc::string::String;
+#[cfg(feature = "compress")]
+use miniz_oxide::inflate::decompress_to_vec;
+#[cfg(all(feature = "compress", not(feature = "std"), feature = "spin"))]
+use spin::Lazy;
+#[cfg(all(feature = "compress", feature = "std"))]
+use std::sync::LazyLock as Lazy;
+
+#[cfg(all(feature = "compress", not(any(feature = "std", feature = "spin"))))]
+compile_error!("the `compress` feature requires `std` or `spin`");
+
+#[cfg(not(feature = "compress"))]
+macro_rules! compiled {
+    ($name:literal) => {
+        CompactTrie::from_parts(
+            include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".nodes")),
+            include_str!(concat!(env!("OUT_DIR"), "/", $name, ".values")),
+        )
+    };
+}
+
+/// With the `compress` feature, the build script deflates the sections, and a dictionary is
+/// inflated the first time it is used.
+#[cfg(feature = "compress")]
+macro_rules! compiled {
+    ($name:literal) => {
+        Lazy::new(|| {
+            inflate(
+                include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".nodes")),
+                include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".values")),
+            )
+        })
+    };
+}
+
+#[cfg(not(feature = "compress"))]
+type Compiled = CompactTrie<'static>;
+#[cfg(feature = "compress")]
+type Compiled = Lazy<CompactTrie<'static>>;
+
+/// Decompresses the sections of a compiled dictionary. They are leaked, as the dictionary lives in
+/// a static anyway.
+#[cfg(feature = "compress")]
+fn inflate(nodes: &[u8], values: &[u8]) -> CompactTrie<'static> {
+    let nodes = decompress_to_vec(nodes).unwrap().leak();
+    let values = String::from_utf8(decompress_to_vec(values).unwrap())
+        .unwrap()
+        .leak();
+
+    CompactTrie::from_parts(nodes, values)
+}
+
+#[cfg(feature = "st")]
+pub(super) static S2T_DICT: Compiled = compiled!("S2T");
+#[cfg(feature = "ts")]
+pub(super) static T2S_DICT: Compiled = compiled!("T2S");
+#[cfg(feature = "tw")]
+pub(super) static T2TW_VARIANTS_DICT: Compiled = compiled!("T2TW_VARIANTS");
+#[cfg(all(feature = "tw", any(feature = "st", feature = "hk")))]
+pub(super) static T2TW_PHRASES_DICT: Compiled = compiled!("T2TW_PHRASES");
+#[cfg(feature = "tw")]
+pub(super) static TW2T_VARIANTS_DICT: Compiled = compiled!("TW2T_VARIANTS");
+#[cfg(all(feature = "tw", feature = "ts"))]
+pub(super) static TW2T_PHRASES_VARIANTS_DICT: Compiled = compiled!("TW2T_PHRASES_VARIANTS");
+#[cfg(feature = "hk")]
+pub(super) static T2HK_VARIANTS_DICT: Compiled = compiled!("T2HK_VARIANTS");
+#[cfg(feature = "hk")]
+pub(super) static HK2T_VARIANTS_DICT: Compiled = compiled!("HK2T_VARIANTS");
+#[cfg(feature = "jp")]
+pub(super) static T2JP_VARIANTS_DICT: Compiled = compiled!("T2JP_VARIANTS");
+#[cfg(feature = "jp")]
+pub(super) static JP2T_DICT: Compiled = compiled!("JP2T");
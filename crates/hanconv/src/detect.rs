@@ -0,0 +1,357 @@
+/// The variants and the computation of [`EXCLUDED`], shared with the build script.
+#[allow(dead_code)]
+mod table;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+pub use table::Variant;
+
+/// The probability that a char a variant does not use appears in text of that variant anyway,
+/// e.g. in a quotation or a name.
+const ERROR_RATE: f64 = 0.05;
+
+/// The likelihood of Japanese relative to the other variants when the text has no kana, as
+/// Japanese is rarely written with Han chars alone.
+const NO_KANA: f64 = 0.2;
+
+/// The variants that text is likely written in, as returned by [`detect`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// The number of Han chars in the text.
+    pub han: usize,
+    /// The number of chars that some variant does not use, including kana.
+    pub evidence: usize,
+    /// The confidence in each variant, from the most to the least likely, summing to 1. Variants
+    /// that are equally likely keep the order of [`Variant::ALL`].
+    ///
+    /// Empty if the text contains no Han chars.
+    pub scores: Vec<(Variant, f64)>,
+}
+
+impl Detection {
+    /// Returns the most likely variant, or `None` if the text contains no Han chars.
+    pub fn best(&self) -> Option<Variant> {
+        self.scores.first().map(|&(variant, _)| variant)
+    }
+
+    /// Returns the confidence in `variant`.
+    pub fn score(&self, variant: Variant) -> f64 {
+        self.scores
+            .iter()
+            .find(|&&(v, _)| v == variant)
+            .map_or(0.0, |&(_, score)| score)
+    }
+}
+
+/// The cost of changing variant between two segments of [`detect_segments`], in chars that the
+/// variant of a segment does not use.
+const SWITCH_COST: usize = 2;
+
+/// For each char, the variants that do not use it, sorted by char. The build script computes
+/// it from the dictionaries, so that they are not needed at run time.
+static EXCLUDED: &[(char, u8)] = include!(concat!(env!("OUT_DIR"), "/EXCLUDED.rs"));
+
+/// Returns whether `c` is a CJK ideograph.
+fn is_han(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3007}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+            | '\u{30000}'..='\u{323AF}'
+    )
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}') && c != '\u{30FB}'
+}
+
+/// Returns the variants that do not use `c`, or `None` if `c` is neither Han nor kana.
+fn mask(c: char) -> Option<u8> {
+    if is_han(c) {
+        Some(
+            EXCLUDED
+                .binary_search_by_key(&c, |&(c, _)| c)
+                .map_or(0, |i| EXCLUDED[i].1),
+        )
+    } else if is_kana(c) {
+        Some(!Variant::Japanese.bit())
+    } else {
+        None
+    }
+}
+
+/// Detects which variant of Chinese characters `text` is written in.
+///
+/// Chars that a variant does not use count against it: for example, chars that T2S converts
+/// count against Simplified Chinese, and the standard forms that TWVariants converts count
+/// against Taiwan. Kana counts against every variant except Japanese, and its absence counts
+/// against Japanese. Evidence from the dictionaries of a disabled family is not available.
+pub fn detect(text: impl AsRef<str>) -> Detection {
+    let mut han = 0;
+    let mut kana = 0;
+    let mut evidence = 0;
+    let mut mismatches = [0usize; Variant::ALL.len()];
+
+    for c in text.as_ref().chars() {
+        let Some(mask) = mask(c) else {
+            continue;
+        };
+
+        if is_han(c) {
+            han += 1;
+        } else {
+            kana += 1;
+        }
+
+        if mask != 0 {
+            evidence += 1;
+        }
+
+        for (variant, mismatches) in Variant::ALL.iter().zip(&mut mismatches) {
+            if mask & variant.bit() != 0 {
+                *mismatches += 1;
+            }
+        }
+    }
+
+    if han == 0 {
+        return Detection {
+            han,
+            evidence,
+            scores: Vec::new(),
+        };
+    }
+
+    // The likelihood of each variant relative to the most likely one. Beyond 64 mismatches more
+    // the likelihood is negligible.
+    let min = mismatches.iter().copied().min().unwrap_or_default();
+    let mut likelihoods = mismatches.map(|n| (min..n).take(64).fold(1.0, |p, _| p * ERROR_RATE));
+    if kana == 0 {
+        likelihoods[Variant::Japanese as usize] *= NO_KANA;
+    }
+    let total = likelihoods.iter().sum::<f64>();
+
+    let mut scores = Variant::ALL
+        .into_iter()
+        .zip(likelihoods.map(|p| p / total))
+        .collect::<Vec<_>>();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    Detection {
+        han,
+        evidence,
+        scores,
+    }
+}
+
+/// A run of text written in one variant, as returned by [`detect_segments`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantSegment {
+    /// The byte range of the segment in the text.
+    pub range: Range<usize>,
+    /// The variant of the segment, or `None` if the text contains no Han chars.
+    pub variant: Option<Variant>,
+}
+
+/// Returns whether a segment may end at `c`.
+fn is_break(c: char) -> bool {
+    c.is_whitespace()
+        || c.is_ascii_punctuation()
+        || matches!(
+            c,
+            '\u{2018}'..='\u{201F}'
+                | '\u{3000}'..='\u{303F}'
+                | '\u{FF01}'..='\u{FF0F}'
+                | '\u{FF1A}'..='\u{FF20}'
+                | '\u{FF3B}'..='\u{FF40}'
+                | '\u{FF5B}'..='\u{FF65}'
+        )
+}
+
+/// Returns whether `c` opens a quotation or bracket, which goes with the text after it.
+fn is_opening(c: char) -> bool {
+    matches!(
+        c,
+        '(' | '['
+            | '{'
+            | '\u{2018}'
+            | '\u{201C}'
+            | '\u{3008}'
+            | '\u{300A}'
+            | '\u{300C}'
+            | '\u{300E}'
+            | '\u{3010}'
+            | '\u{3014}'
+            | '\u{3016}'
+            | '\u{FF08}'
+            | '\u{FF3B}'
+            | '\u{FF5B}'
+    )
+}
+
+/// Splits `text` into contiguous segments that are each written in one variant.
+///
+/// Every char counts against the variants that do not use it, as in [`detect`], and changing
+/// variant between segments costs as much as two such chars, so a few chars of
+/// another variant do not start a segment. A segment ends after the first punctuation and
+/// whitespace between the evidence of two variants, and quotation marks go with the quoted text.
+pub fn detect_segments(text: impl AsRef<str>) -> Vec<VariantSegment> {
+    let text = text.as_ref();
+
+    if text.is_empty() {
+        return Vec::new();
+    }
+
+    let mut han = false;
+    let mut evidence = Vec::new();
+
+    for (i, c) in text.char_indices() {
+        let Some(mask) = mask(c) else {
+            continue;
+        };
+        han |= is_han(c);
+
+        if mask != 0 {
+            evidence.push((i..i + c.len_utf8(), mask));
+        }
+    }
+
+    if !han {
+        return vec![VariantSegment {
+            range: 0..text.len(),
+            variant: None,
+        }];
+    }
+
+    // Viterbi over the chars with evidence: the least cost of the evidence so far ending in each
+    // variant, and for each char the variant of the previous char on the path to each variant.
+    let mut costs = [0; Variant::ALL.len()];
+    let mut previous = Vec::with_capacity(evidence.len());
+
+    for (_, mask) in &evidence {
+        let mut next = costs;
+        let mut from = [0; Variant::ALL.len()];
+
+        for (v, variant) in Variant::ALL.into_iter().enumerate() {
+            let (u, cost) = (0..costs.len())
+                .map(|u| (u, costs[u] + if u == v { 0 } else { SWITCH_COST }))
+                .min_by_key(|&(u, cost)| (cost, u != v, u))
+                .unwrap_or_default();
+
+            next[v] = cost + usize::from(mask & variant.bit() != 0);
+            from[v] = u;
+        }
+
+        costs = next;
+        previous.push(from);
+    }
+
+    let mut v = (0..costs.len())
+        .min_by_key(|&v| costs[v])
+        .unwrap_or_default();
+    let mut states = vec![0; evidence.len()];
+
+    for (i, from) in previous.iter().enumerate().rev() {
+        states[i] = v;
+        v = from[v];
+    }
+
+    let mut segments: Vec<VariantSegment> = Vec::new();
+    let mut start = 0;
+
+    for (i, &v) in states.iter().enumerate() {
+        if i == 0 || states[i - 1] == v {
+            continue;
+        }
+
+        // End the segment after the first run of punctuation and whitespace between the evidence
+        // of the two variants, or before an opening quotation.
+        let gap = evidence[i - 1].0.end..evidence[i].0.start;
+        let mut end = gap.end;
+        let mut broken = false;
+
+        for (j, c) in text[gap.clone()].char_indices() {
+            if is_opening(c) || (broken && !is_break(c)) {
+                end = gap.start + j;
+                break;
+            }
+            broken |= is_break(c);
+        }
+
+        segments.push(VariantSegment {
+            range: start..end,
+            variant: Some(Variant::ALL[states[i - 1]]),
+        });
+        start = end;
+    }
+
+    segments.push(VariantSegment {
+        range: start..text.len(),
+        variant: Some(Variant::ALL[states.last().copied().unwrap_or_default()]),
+    });
+
+    segments
+}
+
+#[cfg(all(test, feature = "tw", feature = "hk", feature = "jp"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect("我们的头发").best(), Some(Variant::Simplified));
+        assert_eq!(detect("我們爲什麼").best(), Some(Variant::Traditional));
+        assert_eq!(detect("這裡的人們").best(), Some(Variant::Taiwan));
+        assert_eq!(detect("啟發").best(), Some(Variant::Taiwan));
+        assert_eq!(
+            detect("啟發").score(Variant::HongKong),
+            detect("啟發").score(Variant::Taiwan)
+        );
+        assert_eq!(detect("國際會議の議題です").best(), Some(Variant::Japanese));
+        assert_eq!(detect("国際会議").best(), Some(Variant::Japanese));
+
+        let detection = detect("我们的头发");
+        assert_eq!(detection.han, 5);
+        assert!(detection.score(Variant::Simplified) > 0.95);
+        assert!((detection.scores.iter().map(|(_, s)| s).sum::<f64>() - 1.0).abs() < 1e-9);
+
+        let detection = detect("Hello, world!");
+        assert_eq!(detection.han, 0);
+        assert_eq!(detection.best(), None);
+    }
+
+    #[test]
+    fn test_detect_segments() {
+        let text = "他说这个问题：「我們爲什麼要學習這門語言？」然后他们离开了。";
+        let segments = detect_segments(text);
+        let variants = segments.iter().map(|s| s.variant).collect::<Vec<_>>();
+        assert_eq!(
+            variants,
+            [
+                Some(Variant::Simplified),
+                Some(Variant::Traditional),
+                Some(Variant::Simplified)
+            ]
+        );
+        assert_eq!(&text[segments[0].range.clone()], "他说这个问题：");
+        assert_eq!(
+            &text[segments[1].range.clone()],
+            "「我們爲什麼要學習這門語言？」"
+        );
+        assert_eq!(segments.last().unwrap().range.end, text.len());
+
+        assert_eq!(detect_segments("我们的头发").len(), 1);
+        assert_eq!(
+            detect_segments("Hello, world!"),
+            [VariantSegment {
+                range: 0..13,
+                variant: None
+            }]
+        );
+        assert!(detect_segments("").is_empty());
+    }
+}
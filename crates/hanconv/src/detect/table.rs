@@ -0,0 +1,194 @@
+use crate::dict::RawDictionary;
+use alloc::collections::{BTreeMap, BTreeSet};
+
+/// A variant of Chinese characters that [`detect`](super::detect) tells apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Simplified Chinese
+    ///
+    /// 简体中文
+    Simplified,
+    /// Traditional Chinese, in the standard forms of OpenCC
+    ///
+    /// 繁体中文
+    Traditional,
+    /// Traditional Chinese (Taiwan)
+    ///
+    /// 繁体中文（台湾）
+    Taiwan,
+    /// Traditional Chinese (Hong Kong)
+    ///
+    /// 繁体中文（香港）
+    HongKong,
+    /// New Japanese Kanji (Shinjitai)
+    ///
+    /// 日文新字体
+    Japanese,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 5] = [
+        Variant::Simplified,
+        Variant::Traditional,
+        Variant::Taiwan,
+        Variant::HongKong,
+        Variant::Japanese,
+    ];
+
+    pub(super) const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    chars.next().filter(|_| chars.next().is_none())
+}
+
+/// Returns the chars of a char dictionary that are converted to other chars, and the chars they
+/// are converted to.
+fn variants(dictionary: RawDictionary) -> (BTreeSet<char>, BTreeSet<char>) {
+    let mut keys = BTreeSet::new();
+    let mut values = BTreeSet::new();
+
+    for (key, targets) in dictionary.var_iter() {
+        let Some(key) = single_char(key) else {
+            continue;
+        };
+
+        let targets = targets.into_iter().filter_map(single_char);
+        let mut converted = true;
+        for target in targets {
+            if target == key {
+                converted = false;
+            } else {
+                values.insert(target);
+            }
+        }
+
+        if converted {
+            keys.insert(key);
+        }
+    }
+
+    (keys, values)
+}
+
+fn exclude<'a>(
+    excluded: &mut BTreeMap<char, u8>,
+    variant: Variant,
+    chars: impl Iterator<Item = &'a char>,
+) {
+    for &c in chars {
+        *excluded.entry(c).or_default() |= variant.bit();
+    }
+}
+
+/// Returns, for each char, the variants that do not use it. The build script writes it to the
+/// table that [`detect`](super::detect) looks chars up in.
+pub fn excluded() -> BTreeMap<char, u8> {
+    let (simplified, traditional) = variants(RawDictionary::STCharacters);
+    let (traditional_only, _) = variants(RawDictionary::TSCharacters);
+
+    // Chars that appear in the conversion between Simplified and Traditional Chinese.
+    let mut chinese = traditional;
+    chinese.extend(
+        RawDictionary::STCharacters
+            .iter()
+            .filter_map(|(key, _)| single_char(key)),
+    );
+    chinese.extend(
+        RawDictionary::TSCharacters
+            .iter()
+            .filter_map(|(key, _)| single_char(key)),
+    );
+    chinese.extend(
+        RawDictionary::TSCharacters
+            .iter()
+            .filter_map(|(_, value)| single_char(value)),
+    );
+
+    // The forms S2T converts to. Other forms of the same chars are variants.
+    let standard = RawDictionary::STCharacters
+        .iter()
+        .filter_map(|(_, value)| single_char(value))
+        .collect::<BTreeSet<_>>();
+
+    #[cfg(feature = "tw")]
+    let (taiwan_standard, taiwan) = variants(RawDictionary::TWVariants);
+    #[cfg(not(feature = "tw"))]
+    let (taiwan_standard, taiwan) = (BTreeSet::new(), BTreeSet::new());
+
+    #[cfg(feature = "hk")]
+    let (hong_kong_standard, hong_kong) = variants(RawDictionary::HKVariants);
+    #[cfg(not(feature = "hk"))]
+    let (hong_kong_standard, hong_kong) = (BTreeSet::new(), BTreeSet::new());
+
+    #[cfg(feature = "jp")]
+    let (kyujitai, shinjitai) = {
+        let (kyujitai, mut shinjitai) = variants(RawDictionary::JPVariants);
+        shinjitai.extend(
+            RawDictionary::JPShinjitaiCharacters
+                .iter()
+                .filter_map(|(key, _)| single_char(key)),
+        );
+        (kyujitai, shinjitai)
+    };
+    #[cfg(not(feature = "jp"))]
+    let (kyujitai, shinjitai) = (BTreeSet::<char>::new(), BTreeSet::new());
+
+    let japanese_only = shinjitai
+        .difference(&chinese)
+        .copied()
+        .collect::<BTreeSet<_>>();
+    let taiwan_only = taiwan
+        .difference(&standard)
+        .copied()
+        .collect::<BTreeSet<_>>();
+    let hong_kong_only = hong_kong
+        .difference(&standard)
+        .copied()
+        .collect::<BTreeSet<_>>();
+
+    let mut excluded = BTreeMap::new();
+
+    exclude(
+        &mut excluded,
+        Variant::Simplified,
+        traditional_only.iter().chain(&japanese_only),
+    );
+    exclude(
+        &mut excluded,
+        Variant::Traditional,
+        simplified
+            .iter()
+            .chain(&taiwan_only)
+            .chain(&hong_kong_only)
+            .chain(&japanese_only),
+    );
+    exclude(
+        &mut excluded,
+        Variant::Taiwan,
+        simplified
+            .iter()
+            .chain(&taiwan_standard)
+            .chain(hong_kong_only.difference(&taiwan))
+            .chain(&japanese_only),
+    );
+    exclude(
+        &mut excluded,
+        Variant::HongKong,
+        simplified
+            .iter()
+            .chain(&hong_kong_standard)
+            .chain(taiwan_only.difference(&hong_kong))
+            .chain(&japanese_only),
+    );
+    exclude(
+        &mut excluded,
+        Variant::Japanese,
+        kyujitai.iter().chain(simplified.difference(&shinjitai)),
+    );
+
+    excluded
+}
@@ -1,38 +1,92 @@
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
 use RawDictionary::*;
+use alloc::boxed::Box;
+#[cfg(any(feature = "tw", feature = "hk", feature = "jp"))]
+use alloc::vec;
+use alloc::vec::Vec;
 
+/// Parses text in the OpenCC dictionary format into keys and their first values, skipping the
+/// comments and empty lines at the start.
+pub(crate) fn parse(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.lines()
+        .skip_while(|&line| line.starts_with('#') || line.is_empty())
+        .filter_map(|line| {
+            let mut iter = line.split_whitespace();
+
+            if let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+                Some((key, value))
+            } else {
+                None
+            }
+        })
+}
+
+#[allow(clippy::enum_variant_names)]
 pub enum RawDictionary {
+    #[cfg(feature = "st")]
     STCharacters,
+    #[cfg(feature = "st")]
     STPhrases,
+    #[cfg(feature = "ts")]
     TSCharacters,
+    #[cfg(feature = "ts")]
     TSPhrases,
+    #[cfg(feature = "tw")]
     TWPhrases,
+    #[cfg(feature = "tw")]
     TWPhrasesRev,
+    #[cfg(feature = "tw")]
     TWVariants,
+    #[cfg(feature = "tw")]
     TWVariantsRevPhrases,
+    #[cfg(feature = "hk")]
     HKVariants,
+    #[cfg(feature = "hk")]
     HKVariantsRevPhrases,
+    #[cfg(feature = "jp")]
     JPShinjitaiCharacters,
+    #[cfg(feature = "jp")]
     JPShinjitaiPhrases,
+    #[cfg(feature = "jp")]
     JPVariants,
 }
 
 impl RawDictionary {
     pub const fn text(&self) -> &'static str {
-        match self {
+        match *self {
+            #[cfg(feature = "st")]
             STCharacters => include_str!("../data/STCharacters.txt"),
+            #[cfg(feature = "st")]
             STPhrases => include_str!("../data/STPhrases.txt"),
+            #[cfg(feature = "ts")]
             TSCharacters => include_str!("../data/TSCharacters.txt"),
+            #[cfg(feature = "ts")]
             TSPhrases => include_str!("../data/TSPhrases.txt"),
+            #[cfg(feature = "tw")]
             TWPhrases => include_str!("../data/TWPhrases.txt"),
+            #[cfg(feature = "tw")]
             TWPhrasesRev => include_str!("../data/TWPhrasesRev.txt"),
+            #[cfg(feature = "tw")]
             TWVariants => include_str!("../data/TWVariants.txt"),
+            #[cfg(feature = "tw")]
             TWVariantsRevPhrases => include_str!("../data/TWVariantsRevPhrases.txt"),
+            #[cfg(feature = "hk")]
             HKVariants => include_str!("../data/HKVariants.txt"),
+            #[cfg(feature = "hk")]
             HKVariantsRevPhrases => include_str!("../data/HKVariantsRevPhrases.txt"),
+            #[cfg(feature = "jp")]
             JPShinjitaiCharacters => {
                 include_str!("../data/JPShinjitaiCharacters.txt")
             }
+            #[cfg(feature = "jp")]
             JPShinjitaiPhrases => include_str!("../data/JPShinjitaiPhrases.txt"),
+            #[cfg(feature = "jp")]
             JPVariants => include_str!("../data/JPVariants.txt"),
         }
     }
@@ -83,56 +137,154 @@ impl RawDictionary {
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum Dictionary {
+    #[cfg(feature = "st")]
     STCharacters,
+    #[cfg(feature = "st")]
     STPhrases,
+    #[cfg(feature = "ts")]
     TSCharacters,
+    #[cfg(feature = "ts")]
     TSPhrases,
+    #[cfg(feature = "tw")]
     TWPhrases,
+    #[cfg(feature = "tw")]
     TWPhrasesRev,
+    #[cfg(feature = "tw")]
     TWVariants,
+    #[cfg(feature = "tw")]
     TWVariantsRev,
+    #[cfg(feature = "tw")]
     TWVariantsRevPhrases,
+    #[cfg(feature = "hk")]
     HKVariants,
+    #[cfg(feature = "hk")]
     HKVariantsRev,
+    #[cfg(feature = "hk")]
     HKVariantsRevPhrases,
+    #[cfg(feature = "jp")]
     JPShinjitaiCharacters,
+    #[cfg(feature = "jp")]
     JPShinjitaiPhrases,
+    #[cfg(feature = "jp")]
     JPVariants,
+    #[cfg(feature = "jp")]
     JPVariantsRev,
 }
 
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
 macro_rules! iter {
     [$a:expr $(,$b:expr)*] => {
         Box::new($a.iter()$(.chain($b.iter()))*)
     };
 }
 
+#[cfg(any(feature = "tw", feature = "hk", feature = "jp"))]
 macro_rules! inv_iter {
     [$a:expr $(,$b:expr)*] => {
         Box::new($a.inv_iter()$(.chain($b.inv_iter()))*)
     };
 }
 
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
+macro_rules! var_iter {
+    [$a:expr $(,$b:expr)*] => {
+        Box::new($a.var_iter()$(.chain($b.var_iter()))*)
+    };
+}
+
+#[cfg(any(feature = "tw", feature = "hk", feature = "jp"))]
+macro_rules! inv_var_iter {
+    [$a:expr $(,$b:expr)*] => {
+        Box::new($a.inv_iter()$(.chain($b.inv_iter()))*.map(|(key, value)| (key, vec![value])))
+    };
+}
+
 impl Dictionary {
     pub fn iter(&self) -> Box<dyn Iterator<Item = (&'static str, &'static str)>> {
-        match self {
+        match *self {
+            #[cfg(feature = "st")]
             Dictionary::STCharacters => iter![STCharacters],
+            #[cfg(feature = "st")]
             Dictionary::STPhrases => iter![STPhrases],
+            #[cfg(feature = "ts")]
             Dictionary::TSCharacters => iter![TSCharacters],
+            #[cfg(feature = "ts")]
             Dictionary::TSPhrases => iter![TSPhrases],
+            #[cfg(feature = "tw")]
             Dictionary::TWPhrases => iter![TWPhrases],
+            #[cfg(feature = "tw")]
             Dictionary::TWPhrasesRev => iter![TWPhrasesRev],
+            #[cfg(feature = "tw")]
             Dictionary::TWVariants => iter![TWVariants],
+            #[cfg(feature = "tw")]
             Dictionary::TWVariantsRev => inv_iter![TWVariants],
+            #[cfg(feature = "tw")]
             Dictionary::TWVariantsRevPhrases => iter![TWVariantsRevPhrases],
+            #[cfg(feature = "hk")]
             Dictionary::HKVariants => iter![HKVariants],
+            #[cfg(feature = "hk")]
             Dictionary::HKVariantsRev => inv_iter![HKVariants],
+            #[cfg(feature = "hk")]
             Dictionary::HKVariantsRevPhrases => iter![HKVariantsRevPhrases],
+            #[cfg(feature = "jp")]
             Dictionary::JPShinjitaiCharacters => iter![JPShinjitaiCharacters],
+            #[cfg(feature = "jp")]
             Dictionary::JPShinjitaiPhrases => iter![JPShinjitaiPhrases],
+            #[cfg(feature = "jp")]
             Dictionary::JPVariants => iter![JPVariants],
+            #[cfg(feature = "jp")]
             Dictionary::JPVariantsRev => inv_iter![JPVariants],
         }
     }
+
+    pub fn var_iter(&self) -> Box<dyn Iterator<Item = (&'static str, Vec<&'static str>)>> {
+        match *self {
+            #[cfg(feature = "st")]
+            Dictionary::STCharacters => var_iter![STCharacters],
+            #[cfg(feature = "st")]
+            Dictionary::STPhrases => var_iter![STPhrases],
+            #[cfg(feature = "ts")]
+            Dictionary::TSCharacters => var_iter![TSCharacters],
+            #[cfg(feature = "ts")]
+            Dictionary::TSPhrases => var_iter![TSPhrases],
+            #[cfg(feature = "tw")]
+            Dictionary::TWPhrases => var_iter![TWPhrases],
+            #[cfg(feature = "tw")]
+            Dictionary::TWPhrasesRev => var_iter![TWPhrasesRev],
+            #[cfg(feature = "tw")]
+            Dictionary::TWVariants => var_iter![TWVariants],
+            #[cfg(feature = "tw")]
+            Dictionary::TWVariantsRev => inv_var_iter![TWVariants],
+            #[cfg(feature = "tw")]
+            Dictionary::TWVariantsRevPhrases => var_iter![TWVariantsRevPhrases],
+            #[cfg(feature = "hk")]
+            Dictionary::HKVariants => var_iter![HKVariants],
+            #[cfg(feature = "hk")]
+            Dictionary::HKVariantsRev => inv_var_iter![HKVariants],
+            #[cfg(feature = "hk")]
+            Dictionary::HKVariantsRevPhrases => var_iter![HKVariantsRevPhrases],
+            #[cfg(feature = "jp")]
+            Dictionary::JPShinjitaiCharacters => var_iter![JPShinjitaiCharacters],
+            #[cfg(feature = "jp")]
+            Dictionary::JPShinjitaiPhrases => var_iter![JPShinjitaiPhrases],
+            #[cfg(feature = "jp")]
+            Dictionary::JPVariants => var_iter![JPVariants],
+            #[cfg(feature = "jp")]
+            Dictionary::JPVariantsRev => inv_var_iter![JPVariants],
+        }
+    }
 }
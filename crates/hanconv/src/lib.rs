@@ -1,14 +1,74 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod builder;
+mod compact;
+#[cfg(feature = "std")]
+mod config;
 mod conv;
+#[cfg(all(feature = "st", feature = "ts"))]
+mod detect;
 mod dict;
+mod loader;
+mod matcher;
+mod ocd2;
+mod protect;
+mod roundtrip;
+mod span;
+mod stage;
+#[cfg(feature = "std")]
+mod stream;
 mod trie;
 
-pub use conv::{
-    Converter,
-    Converters::{self, *},
-    hk2s, hk2t, jp2t, s2hk, s2t, s2tw, s2twp, t2hk, t2jp, t2s, t2tw, tw2s, tw2sp, tw2t,
-};
-pub use dict::{
-    Dictionary::{self, *},
-    RawDictionary,
-};
-pub use trie::{Node, Trie};
+pub use builder::ConverterBuilder;
+pub use compact::{CompactTrie, FormatError, MAGIC, VERSION};
+#[cfg(feature = "std")]
+pub use config::ConfigError;
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
+pub use conv::Converters::*;
+#[cfg(all(
+    any(feature = "std", feature = "spin"),
+    any(
+        feature = "st",
+        feature = "ts",
+        feature = "tw",
+        feature = "hk",
+        feature = "jp"
+    )
+))]
+pub use conv::helpers::*;
+pub use conv::{BetweenError, Converter, Converters, Locale, ParseConvertersError};
+#[cfg(all(feature = "st", feature = "ts"))]
+pub use detect::{Detection, Variant, VariantSegment, detect, detect_segments};
+#[cfg(any(
+    feature = "st",
+    feature = "ts",
+    feature = "tw",
+    feature = "hk",
+    feature = "jp"
+))]
+pub use dict::Dictionary::*;
+pub use dict::{Dictionary, RawDictionary};
+#[cfg(feature = "std")]
+pub use loader::LoadError;
+pub use loader::{TextError, TextErrorKind, TextLoader};
+pub use matcher::Matcher;
+pub use ocd2::read_ocd2;
+#[cfg(feature = "std")]
+pub use ocd2::write_ocd2;
+#[cfg(feature = "std")]
+pub use protect::Detector;
+pub use protect::Protection;
+pub use roundtrip::{RoundTripDifference, round_trip};
+pub use span::{Candidates, Span};
+pub use stage::Stage;
+#[cfg(feature = "std")]
+pub use stream::{ConvertReader, ConvertWriter};
+pub use trie::{Entry, Iter, Node, OccupiedEntry, Trie, VacantEntry};
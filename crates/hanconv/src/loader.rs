@@ -0,0 +1,342 @@
+use crate::trie::Trie;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+use unicode_normalization::is_nfc;
+
+/// Why a line of a dictionary in the OpenCC text format is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextErrorKind {
+    /// The key is not followed by a tab.
+    MissingTab,
+    /// The line starts with a tab.
+    EmptyKey,
+    /// The key contains whitespace.
+    WhitespaceInKey,
+    /// The line has no values, or two spaces or a trailing space leave an empty one.
+    EmptyValue,
+    /// The key was already defined on the given line.
+    DuplicateKey(usize),
+    /// A comment follows entries. Comments are only allowed at the start of the file.
+    Comment,
+    /// The key is not in Unicode Normalization Form C, so it would not match normalized text.
+    NotNfc,
+}
+
+impl Display for TextErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TextErrorKind::MissingTab => write!(f, "expected a tab after the key"),
+            TextErrorKind::EmptyKey => write!(f, "empty key"),
+            TextErrorKind::WhitespaceInKey => write!(f, "whitespace in key"),
+            TextErrorKind::EmptyValue => write!(f, "empty value"),
+            TextErrorKind::DuplicateKey(line) => write!(f, "key already defined on line {line}"),
+            TextErrorKind::Comment => write!(f, "comment after the first entry"),
+            TextErrorKind::NotNfc => write!(f, "key is not in NFC"),
+        }
+    }
+}
+
+/// An invalid line of a dictionary in the OpenCC text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextError {
+    /// The file named with [`TextLoader::file`], if any.
+    pub file: Option<String>,
+    /// The line, starting at 1.
+    pub line: usize,
+    /// The column in chars, starting at 1.
+    pub column: usize,
+    pub kind: TextErrorKind,
+}
+
+impl Display for TextError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl Error for TextError {}
+
+/// An error loading a dictionary file in the OpenCC text format.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, io::Error),
+    Text(TextError),
+}
+
+#[cfg(feature = "std")]
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            LoadError::Text(err) => write!(f, "invalid dictionary {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(_, err) => Some(err),
+            LoadError::Text(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<TextError> for LoadError {
+    fn from(err: TextError) -> Self {
+        LoadError::Text(err)
+    }
+}
+
+/// Loads dictionaries in the OpenCC text format, a key, a tab and values separated by spaces on
+/// each line, after optional comments starting with `#` at the start of the file.
+///
+/// By default every line is validated and the first invalid one is reported as a [`TextError`].
+/// In lenient mode, keys and values are separated by any whitespace as in
+/// [`Trie::from_text`], comments are skipped anywhere, keys are not checked for NFC, lines
+/// without a value are skipped and the last entry of a duplicate key is kept, as in
+/// [`Trie::from_text`].
+#[derive(Debug, Clone, Default)]
+pub struct TextLoader {
+    file: Option<String>,
+    lenient: bool,
+}
+
+impl TextLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the file in errors.
+    pub fn file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Skips invalid lines instead of failing.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Parses `text` into keys and their values in order.
+    pub fn parse<'t>(&self, text: &'t str) -> Result<Vec<(&'t str, Vec<&'t str>)>, TextError> {
+        let entries = self.entries(text).collect::<Result<Vec<_>, _>>()?;
+        if !self.lenient {
+            return Ok(entries);
+        }
+
+        let last = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (key, i))
+            .collect::<Trie<_>>();
+        Ok(entries
+            .into_iter()
+            .enumerate()
+            .filter(|(i, (key, _))| last.get(key) == Some(i))
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+
+    /// Lists every invalid line of `text`. Always empty in lenient mode.
+    pub fn validate(&self, text: &str) -> Vec<TextError> {
+        self.entries(text).filter_map(Result::err).collect()
+    }
+
+    /// Builds a trie from `text`, mapping each key to its first value.
+    pub fn load(&self, text: &str) -> Result<Trie<String>, TextError> {
+        Ok(self
+            .parse(text)?
+            .into_iter()
+            .map(|(key, values)| (key, values[0].to_string()))
+            .collect())
+    }
+
+    /// Builds a trie from a file like [`load`](TextLoader::load), naming the file in errors
+    /// unless it is already named.
+    #[cfg(feature = "std")]
+    pub fn load_file(&self, path: impl AsRef<Path>) -> Result<Trie<String>, LoadError> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|err| LoadError::Io(path.to_path_buf(), err))?;
+
+        let loader = match self.file {
+            Some(_) => self.clone(),
+            None => self.clone().file(path.display().to_string()),
+        };
+        Ok(loader.load(&text)?)
+    }
+
+    fn entries<'t>(
+        &self,
+        text: &'t str,
+    ) -> impl Iterator<Item = Result<(&'t str, Vec<&'t str>), TextError>> {
+        let mut seen = Trie::default();
+        let mut header = true;
+
+        text.lines().enumerate().filter_map(move |(i, line)| {
+            let line_number = i + 1;
+            let error = |column, kind| {
+                Some(Err(TextError {
+                    file: self.file.clone(),
+                    line: line_number,
+                    column,
+                    kind,
+                }))
+            };
+
+            if line.is_empty() {
+                return None;
+            }
+            if line.starts_with('#') {
+                return match header || self.lenient {
+                    true => None,
+                    false => error(1, TextErrorKind::Comment),
+                };
+            }
+            header = false;
+
+            let (key, values) = match self.lenient {
+                true => {
+                    let mut fields = line.split_whitespace();
+                    let key = fields.next()?;
+                    let values = fields.collect::<Vec<_>>();
+
+                    if values.is_empty() {
+                        return None;
+                    }
+                    (key, values)
+                }
+                false => match validate_line(line) {
+                    Ok(entry) => entry,
+                    Err((column, kind)) => return error(column, kind),
+                },
+            };
+
+            if !self.lenient {
+                if let Some(&first) = seen.get(key) {
+                    return error(1, TextErrorKind::DuplicateKey(first));
+                }
+                seen.insert(key, line_number);
+            }
+
+            Some(Ok((key, values)))
+        })
+    }
+}
+
+/// Splits a line into its key and values, or returns the column and kind of its first error.
+fn validate_line(line: &str) -> Result<(&str, Vec<&str>), (usize, TextErrorKind)> {
+    let column = |i: usize| line[..i].chars().count() + 1;
+
+    let Some((key, rest)) = line.split_once('\t') else {
+        return Err(match line.find(char::is_whitespace) {
+            Some(i) => (column(i), TextErrorKind::MissingTab),
+            None => (column(line.len()), TextErrorKind::MissingTab),
+        });
+    };
+
+    if key.is_empty() {
+        return Err((1, TextErrorKind::EmptyKey));
+    }
+    if let Some(i) = key.find(char::is_whitespace) {
+        return Err((column(i), TextErrorKind::WhitespaceInKey));
+    }
+    if !is_nfc(key) {
+        return Err((1, TextErrorKind::NotNfc));
+    }
+
+    let mut values = Vec::new();
+    let mut start = key.len() + 1;
+
+    for value in rest.split(char::is_whitespace) {
+        if value.is_empty() {
+            return Err((column(start), TextErrorKind::EmptyValue));
+        }
+        values.push(value);
+        start += value.len();
+        start += line[start..].chars().next().map_or(0, char::len_utf8);
+    }
+
+    Ok((key, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_text_loader() {
+        let text = "# Glossary\n\n云服务\t雲端服務 雲服務\n鼠标\t滑鼠\n";
+        let entries = TextLoader::new().parse(text).unwrap();
+        assert_eq!(entries[0], ("云服务", vec!["雲端服務", "雲服務"]));
+        assert_eq!(
+            TextLoader::new().load(text).unwrap().convert("云服务"),
+            "雲端服務"
+        );
+
+        let invalid = "云服务\t雲端服務\n鼠标 滑鼠\n# Note\n软件\t\n软件\t軟體  軟件\n鼠標\t滑鼠\n云服务\t雲服務\n\
+                       \u{1100}\u{1161}\t가\n";
+        let errors = TextLoader::new().file("glossary.txt").validate(invalid);
+        let errors = errors
+            .iter()
+            .map(|err| (err.line, err.column, err.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (2, 3, TextErrorKind::MissingTab),
+                (3, 1, TextErrorKind::Comment),
+                (4, 4, TextErrorKind::EmptyValue),
+                (5, 7, TextErrorKind::EmptyValue),
+                (7, 1, TextErrorKind::DuplicateKey(1)),
+                (8, 1, TextErrorKind::NotNfc),
+            ]
+        );
+
+        let err = TextLoader::new().file("glossary.txt").parse(invalid);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "glossary.txt:2:3: expected a tab after the key"
+        );
+
+        let lenient = TextLoader::new().lenient(true).load(invalid).unwrap();
+        assert_eq!(lenient.get("鼠标").map(String::as_str), Some("滑鼠"));
+        assert_eq!(lenient.get("云服务").map(String::as_str), Some("雲服務"));
+        assert_eq!(lenient.get("软件").map(String::as_str), Some("軟體"));
+
+        // Like `Trie::from_text`, lenient mode keeps the last entry of a duplicate key.
+        let parsed = TextLoader::new().lenient(true).parse(invalid).unwrap();
+        assert_eq!(parsed.iter().filter(|(key, _)| *key == "云服务").count(), 1);
+        assert_eq!(
+            Trie::from_text(invalid).get("云服务"),
+            lenient.get("云服务")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "st")]
+    fn test_bundled_dictionaries() {
+        use crate::RawDictionary;
+
+        for dictionary in [RawDictionary::STCharacters, RawDictionary::STPhrases] {
+            assert_eq!(TextLoader::new().validate(dictionary.text()), []);
+        }
+    }
+}
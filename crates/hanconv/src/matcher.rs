@@ -0,0 +1,123 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(all(not(feature = "std"), feature = "spin"))]
+use spin::Lazy;
+#[cfg(feature = "std")]
+use std::sync::LazyLock as Lazy;
+
+/// Longest-prefix lookup shared by the dictionaries a [`Converter`](crate::Converter) chains.
+pub trait Matcher: Send + Sync {
+    /// Returns the length in chars of the longest key.
+    fn depth(&self) -> usize;
+
+    /// Returns the value of the longest key that `s` starts with, together with the length of the
+    /// key in bytes.
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)>;
+
+    /// Returns the value of the longest key that `s` starts with followed by its alternatives,
+    /// together with the length of the key in bytes.
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        self.longest_match(s).map(|(value, len)| (vec![value], len))
+    }
+
+    /// Iterates over all entries.
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_>;
+
+    /// Replaces the longest matches in `input` from left to right, keeping unmatched chars.
+    fn convert(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(c) = rest.chars().next() {
+            if let Some((value, len)) = self.longest_match(rest) {
+                output.push_str(value);
+                rest = &rest[len..];
+            } else {
+                output.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+        output
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for &M {
+    fn depth(&self) -> usize {
+        (**self).depth()
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        (**self).longest_match(s)
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        (**self).candidates(s)
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        (**self).entries()
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for Box<M> {
+    fn depth(&self) -> usize {
+        (**self).depth()
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        (**self).longest_match(s)
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        (**self).candidates(s)
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        (**self).entries()
+    }
+}
+
+/// Shares the dictionary between converters, which keep it alive until the last one is dropped.
+#[cfg(target_has_atomic = "ptr")]
+impl<M: Matcher + ?Sized> Matcher for Arc<M> {
+    fn depth(&self) -> usize {
+        (**self).depth()
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        (**self).longest_match(s)
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        (**self).candidates(s)
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        (**self).entries()
+    }
+}
+
+/// Initializes the dictionary the first time it is used.
+#[cfg(any(feature = "std", feature = "spin"))]
+impl<M: Matcher> Matcher for Lazy<M> {
+    fn depth(&self) -> usize {
+        (**self).depth()
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        (**self).longest_match(s)
+    }
+
+    fn candidates(&self, s: &str) -> Option<(Vec<&str>, usize)> {
+        (**self).candidates(s)
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        (**self).entries()
+    }
+}
@@ -0,0 +1,702 @@
+use crate::compact::FormatError;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// Header at the start of an OpenCC `.ocd2` dictionary.
+const HEADER: &[u8] = b"OPENCC_MARISA_0.2.5";
+
+/// Header at the start of a serialized marisa-trie.
+const MARISA_HEADER: &[u8; 16] = b"We love Marisa.\0";
+
+/// Flags of the tries written by [`write_ocd2`]: a single trie with text tails, label order and
+/// the normal cache level.
+#[cfg(feature = "std")]
+const MARISA_FLAGS: u32 = 1 | 0x1000 | 0x10000 | 0x200;
+
+/// Reads the entries of an OpenCC `.ocd2` dictionary.
+///
+/// An `.ocd2` file consists of a header, a marisa-trie holding the keys, and the values of each
+/// key in the order of the key IDs assigned by the trie. The entries are returned in that order,
+/// which is usually not sorted.
+pub fn read_ocd2(bytes: &[u8]) -> Result<Vec<(String, Vec<String>)>, FormatError> {
+    let bytes = bytes
+        .strip_prefix(HEADER)
+        .ok_or(FormatError::InvalidMagic)?;
+    let mut reader = Reader(bytes);
+
+    if reader.take(MARISA_HEADER.len())? != MARISA_HEADER {
+        return Err(FormatError::InvalidMagic);
+    }
+    let trie = LoudsTrie::read(&mut reader)?;
+    let keys = trie.keys()?;
+
+    let count = reader.u32()? as usize;
+    let len = reader.u32()? as usize;
+    let mut buf = reader.take(len)?;
+
+    if count != keys.len() {
+        return Err(FormatError::Corrupted);
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for key in keys {
+        let values = (0..reader.u16()?)
+            .map(|_| {
+                let len = reader.u16()? as usize;
+                let value = buf.get(..len).ok_or(FormatError::Corrupted)?;
+                buf = &buf[len..];
+
+                // Values are stored with a terminating NUL included in their length.
+                let value = value.strip_suffix(b"\0").ok_or(FormatError::Corrupted)?;
+                Ok(str::from_utf8(value)?.to_string())
+            })
+            .collect::<Result<_, FormatError>>()?;
+
+        entries.push((key, values));
+    }
+
+    Ok(entries)
+}
+
+/// Writes keys and their values as an OpenCC `.ocd2` dictionary.
+///
+/// Later entries override earlier ones with the same key, and empty keys and entries without
+/// values are ignored.
+#[cfg(feature = "std")]
+pub fn write_ocd2<K, I, V>(
+    entries: impl IntoIterator<Item = (K, I)>,
+    mut writer: impl Write,
+) -> io::Result<()>
+where
+    K: AsRef<str>,
+    I: IntoIterator<Item = V>,
+    V: AsRef<str>,
+{
+    let entries = entries
+        .into_iter()
+        .map(|(key, values)| {
+            let values = values
+                .into_iter()
+                .map(|value| value.as_ref().to_string())
+                .collect::<Vec<_>>();
+            (key.as_ref().as_bytes().to_vec(), values)
+        })
+        .filter(|(key, values)| !key.is_empty() && !values.is_empty())
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let mut bytes = HEADER.to_vec();
+    bytes.extend_from_slice(MARISA_HEADER);
+    let ids = write_trie(&mut bytes, entries.iter().map(|(key, _)| key.as_slice()));
+
+    let mut values = vec![&[][..]; entries.len()];
+    for ((_, entry), id) in entries.iter().zip(ids) {
+        values[id] = entry.as_slice();
+    }
+
+    let buf = values
+        .iter()
+        .flat_map(|values| values.iter())
+        .flat_map(|value| value.bytes().chain([0]))
+        .collect::<Vec<_>>();
+
+    bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(buf.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&buf);
+    for values in values {
+        bytes.extend_from_slice(&(values.len() as u16).to_le_bytes());
+        for value in values {
+            bytes.extend_from_slice(&(value.len() as u16 + 1).to_le_bytes());
+        }
+    }
+
+    writer.write_all(&bytes)
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FormatError> {
+        if self.0.len() < len {
+            return Err(FormatError::Truncated);
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, FormatError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, FormatError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, FormatError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a marisa vector: its length in bytes, its elements and padding to 8 bytes.
+    fn vector(&mut self) -> Result<&'a [u8], FormatError> {
+        let len = usize::try_from(self.u64()?).map_err(|_| FormatError::Corrupted)?;
+        let bytes = self.take(len)?;
+        self.take((8 - len % 8) % 8)?;
+        Ok(bytes)
+    }
+
+    /// Reads a bit vector, skipping its rank and select indices.
+    fn bits(&mut self) -> Result<Bits<'a>, FormatError> {
+        let units = self.vector()?;
+        let len = self.u32()? as usize;
+        self.u32()?;
+        self.vector()?;
+        self.vector()?;
+        self.vector()?;
+
+        if units.len() * 8 < len {
+            return Err(FormatError::Corrupted);
+        }
+        Ok(Bits { units, len })
+    }
+
+    /// Reads a vector of integers packed with the same number of bits.
+    fn flat(&mut self) -> Result<Vec<u32>, FormatError> {
+        let units = self.vector()?;
+        let value_size = self.u32()? as usize;
+        self.u32()?;
+        let len = usize::try_from(self.u64()?).map_err(|_| FormatError::Corrupted)?;
+
+        if value_size > 32 || units.len() * 8 < value_size * len {
+            return Err(FormatError::Corrupted);
+        }
+
+        let bits = Bits {
+            units,
+            len: units.len() * 8,
+        };
+        Ok((0..len)
+            .map(|i| {
+                (0..value_size).fold(0, |value, j| {
+                    value | (bits.get(i * value_size + j) as u32) << j
+                })
+            })
+            .collect())
+    }
+}
+
+struct Bits<'a> {
+    units: &'a [u8],
+    len: usize,
+}
+
+impl Bits<'_> {
+    fn get(&self, i: usize) -> bool {
+        self.units[i / 8] >> (i % 8) & 1 == 1
+    }
+
+    fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+}
+
+/// A level of a marisa-trie, with the strings of multi-byte edges stored either in the next level
+/// or in the tail.
+struct LoudsTrie {
+    parents: Vec<usize>,
+    terminals: Vec<bool>,
+    /// The link of each node, or `None` if the edge to the node is a single byte.
+    links: Vec<Option<usize>>,
+    bases: Vec<u8>,
+    tail: Tail,
+    next: Option<Box<LoudsTrie>>,
+    num_l1_nodes: usize,
+}
+
+enum Tail {
+    Text(Vec<u8>),
+    Binary(Vec<u8>, Vec<bool>),
+}
+
+impl LoudsTrie {
+    fn read(reader: &mut Reader) -> Result<Self, FormatError> {
+        let louds = reader.bits()?;
+        let terminals = reader.bits()?.iter().collect::<Vec<_>>();
+        let link_flags = reader.bits()?;
+        let bases = reader.vector()?.to_vec();
+        let extras = reader.flat()?;
+        let buf = reader.vector()?.to_vec();
+        let end_flags = reader.bits()?;
+
+        let has_links = link_flags.iter().any(|flag| flag);
+        let next = if has_links && buf.is_empty() {
+            Some(Box::new(LoudsTrie::read(reader)?))
+        } else {
+            None
+        };
+
+        reader.vector()?;
+        let num_l1_nodes = reader.u32()? as usize;
+        reader.u32()?;
+
+        let tail = if end_flags.len == 0 {
+            Tail::Text(buf)
+        } else {
+            Tail::Binary(buf, end_flags.iter().collect())
+        };
+
+        let len = bases.len();
+        if link_flags.len != len || terminals.len() < len {
+            return Err(FormatError::Corrupted);
+        }
+
+        let mut extras = extras.into_iter();
+        let links = link_flags
+            .iter()
+            .zip(&bases)
+            .map(|(flag, &base)| {
+                if !flag {
+                    return Ok(None);
+                }
+
+                let extra = extras.next().ok_or(FormatError::Corrupted)?;
+                Ok(Some(base as usize | (extra as usize) << 8))
+            })
+            .collect::<Result<Vec<_>, FormatError>>()?;
+
+        // Skip the super-root, then read the children of each node in breadth-first order.
+        let mut bits = louds.iter().skip(2);
+        let mut parents = vec![0; len];
+        let mut next_id = 1;
+
+        for node in 0..len {
+            loop {
+                match bits.next() {
+                    // Children come after their parent, so walking up a level always ends.
+                    Some(true) if next_id <= node => return Err(FormatError::Corrupted),
+                    Some(true) => {
+                        *parents.get_mut(next_id).ok_or(FormatError::Corrupted)? = node;
+                        next_id += 1;
+                    }
+                    Some(false) => break,
+                    None => return Err(FormatError::Corrupted),
+                }
+            }
+        }
+
+        Ok(LoudsTrie {
+            parents,
+            terminals,
+            links,
+            bases,
+            tail,
+            next,
+            num_l1_nodes,
+        })
+    }
+
+    /// Appends the string of the edge leading to `node`.
+    fn edge(&self, node: usize, buf: &mut Vec<u8>) -> Result<(), FormatError> {
+        match self.links[node] {
+            Some(link) => match &self.next {
+                Some(next) => next.restore(link, buf),
+                None => self.tail.restore(link, buf),
+            },
+            None => {
+                buf.push(self.bases[node]);
+                Ok(())
+            }
+        }
+    }
+
+    /// Appends the string that a node of a next level stands for.
+    ///
+    /// Next levels hold reversed strings, so walking up from the node yields the string in order.
+    fn restore(&self, mut node: usize, buf: &mut Vec<u8>) -> Result<(), FormatError> {
+        if node == 0 || node >= self.bases.len() {
+            return Err(FormatError::Corrupted);
+        }
+
+        loop {
+            self.edge(node, buf)?;
+            if node <= self.num_l1_nodes {
+                return Ok(());
+            }
+            node = self.parents[node];
+        }
+    }
+
+    /// Returns the keys in order of their IDs, which follow the order of their nodes.
+    fn keys(&self) -> Result<Vec<String>, FormatError> {
+        let mut keys = vec![Vec::new(); self.bases.len()];
+
+        for node in 1..self.bases.len() {
+            let mut key = keys[self.parents[node]].clone();
+            self.edge(node, &mut key)?;
+            keys[node] = key;
+        }
+
+        keys.into_iter()
+            .zip(&self.terminals)
+            .filter(|&(_, &terminal)| terminal)
+            .map(|(key, _)| Ok(String::from_utf8(key).map_err(|err| err.utf8_error())?))
+            .collect()
+    }
+}
+
+impl Tail {
+    fn restore(&self, offset: usize, buf: &mut Vec<u8>) -> Result<(), FormatError> {
+        match self {
+            Tail::Text(bytes) => {
+                let bytes = bytes.get(offset..).ok_or(FormatError::Corrupted)?;
+                let end = bytes
+                    .iter()
+                    .position(|&b| b == 0)
+                    .ok_or(FormatError::Corrupted)?;
+                buf.extend_from_slice(&bytes[..end]);
+            }
+            Tail::Binary(bytes, end_flags) => {
+                let end = end_flags
+                    .get(offset..)
+                    .and_then(|flags| flags.iter().position(|&flag| flag))
+                    .ok_or(FormatError::Corrupted)?;
+                let bytes = bytes
+                    .get(offset..=offset + end)
+                    .ok_or(FormatError::Corrupted)?;
+                buf.extend_from_slice(bytes);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_vector(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes.resize(bytes.len() + (8 - data.len() % 8) % 8, 0);
+}
+
+#[cfg(feature = "std")]
+fn write_u32s(bytes: &mut Vec<u8>, values: &[u32]) {
+    write_vector(
+        bytes,
+        &values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>(),
+    );
+}
+
+/// Writes a bit vector with the rank and select indices that marisa-trie builds for it.
+#[cfg(feature = "std")]
+fn write_bits(bytes: &mut Vec<u8>, bits: &[bool], select0: bool, select1: bool) {
+    let mut units = vec![0u8; bits.len().div_ceil(64) * 8];
+    for (i, _) in bits.iter().enumerate().filter(|&(_, &bit)| bit) {
+        units[i / 8] |= 1 << (i % 8);
+    }
+
+    // Each rank entry holds the number of 1s before a 512-bit block, and 7 relative counts before
+    // the 64-bit words of the block packed into 7, 8, 8, 9, 9, 9 and 9 bits.
+    let mut ranks = vec![[0u32; 3]; bits.len().div_ceil(512) + 1];
+    let mut select0s = Vec::new();
+    let mut select1s = Vec::new();
+    let mut num_0s = 0u32;
+    let mut num_1s = 0u32;
+
+    let set_rel = |rank: &mut [u32; 3], word: usize, value: u32| match word {
+        1 => rank[1] |= value,
+        2 => rank[1] |= value << 7,
+        3 => rank[1] |= value << 15,
+        4 => rank[1] |= value << 23,
+        5 => rank[2] |= value,
+        6 => rank[2] |= value << 9,
+        7 => rank[2] |= value << 18,
+        _ => unreachable!(),
+    };
+
+    for (i, &bit) in bits.iter().enumerate() {
+        if i.is_multiple_of(64) {
+            let rank = &mut ranks[i / 512];
+            match i / 64 % 8 {
+                0 => rank[0] = num_1s,
+                word => set_rel(rank, word, num_1s - rank[0]),
+            }
+        }
+
+        if bit {
+            if select1 && num_1s.is_multiple_of(512) {
+                select1s.push(i as u32);
+            }
+            num_1s += 1;
+        } else {
+            if select0 && num_0s.is_multiple_of(512) {
+                select0s.push(i as u32);
+            }
+            num_0s += 1;
+        }
+    }
+
+    if !bits.len().is_multiple_of(512) {
+        let rank = &mut ranks[(bits.len() - 1) / 512];
+        for word in (bits.len() - 1) / 64 % 8 + 1..8 {
+            set_rel(rank, word, num_1s - rank[0]);
+        }
+    }
+    ranks.last_mut().unwrap()[0] = num_1s;
+
+    if select0 {
+        select0s.push(bits.len() as u32);
+    }
+    if select1 {
+        select1s.push(bits.len() as u32);
+    }
+
+    write_vector(bytes, &units);
+    bytes.extend_from_slice(&(bits.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&num_1s.to_le_bytes());
+    write_u32s(bytes, &ranks.concat());
+    write_u32s(bytes, &select0s);
+    write_u32s(bytes, &select1s);
+}
+
+/// Writes a marisa-trie of sorted, distinct keys and returns the ID of each key.
+///
+/// Every edge is a single byte, so the trie needs no tail and no next level.
+#[cfg(feature = "std")]
+fn write_trie<'a>(bytes: &mut Vec<u8>, keys: impl Iterator<Item = &'a [u8]>) -> Vec<usize> {
+    let keys = keys.collect::<Vec<_>>();
+
+    let mut louds = vec![true, false];
+    let mut bases = vec![0];
+    let mut terminals = Vec::new();
+    let mut num_l1_nodes = 0;
+
+    let mut queue = VecDeque::from([(0..keys.len(), 0)]);
+    let mut node_terminals = Vec::new();
+
+    while let Some((mut range, depth)) = queue.pop_front() {
+        if range.start < range.end && keys[range.start].len() == depth {
+            node_terminals.push(Some(range.start));
+            range.start += 1;
+        } else {
+            node_terminals.push(None);
+        }
+
+        let mut children = 0;
+        while range.start < range.end {
+            let label = keys[range.start][depth];
+            let end = range.start
+                + keys[range.clone()]
+                    .iter()
+                    .position(|key| key[depth] != label)
+                    .unwrap_or(range.len());
+
+            louds.push(true);
+            bases.push(label);
+            queue.push_back((range.start..end, depth + 1));
+            range.start = end;
+            children += 1;
+        }
+        louds.push(false);
+
+        if node_terminals.len() == 1 {
+            num_l1_nodes = children;
+        }
+    }
+    louds.push(false);
+
+    let mut ids = vec![0; keys.len()];
+    for (id, index) in node_terminals.iter().flatten().enumerate() {
+        ids[*index] = id;
+    }
+    terminals.extend(node_terminals.iter().map(Option::is_some));
+    terminals.push(false);
+
+    write_bits(bytes, &louds, true, true);
+    write_bits(bytes, &terminals, false, true);
+    write_bits(bytes, &vec![false; bases.len()], false, false);
+    write_vector(bytes, &bases);
+
+    // No extras, no tail and an empty cache.
+    write_vector(bytes, &[]);
+    bytes.extend_from_slice(&[0; 16]);
+    write_vector(bytes, &[]);
+    write_vector(bytes, &[]);
+    bytes.extend_from_slice(&[0; 8]);
+    write_vector(bytes, &[]);
+    write_vector(bytes, &[]);
+    write_vector(bytes, &[]);
+
+    let mut cache_size = 256;
+    while cache_size < keys.len() / 0x200 {
+        cache_size *= 2;
+    }
+    let empty = [u32::MAX, u32::MAX, f32::MIN_POSITIVE.to_bits()];
+    write_u32s(bytes, &empty.repeat(cache_size));
+
+    bytes.extend_from_slice(&(num_l1_nodes as u32).to_le_bytes());
+    bytes.extend_from_slice(&MARISA_FLAGS.to_le_bytes());
+
+    ids
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "st")]
+    fn test_ocd2() {
+        use crate::Dictionary;
+
+        let entries = Dictionary::STCharacters
+            .var_iter()
+            .chain(Dictionary::STPhrases.var_iter().take(1000))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut bytes = Vec::new();
+        write_ocd2(entries.clone(), &mut bytes).unwrap();
+
+        let mut read = read_ocd2(&bytes).unwrap();
+        read.sort();
+        assert!(
+            read.iter()
+                .map(|(key, values)| (key.as_str(), values.iter().map(String::as_str).collect()))
+                .eq(entries)
+        );
+
+        assert!(matches!(
+            read_ocd2(&bytes[..bytes.len() - 1]),
+            Err(FormatError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_ocd2_tail() {
+        // A root with one child whose edge "ab" is stored in a text tail.
+        let mut bytes = HEADER.to_vec();
+        bytes.extend_from_slice(MARISA_HEADER);
+        write_bits(
+            &mut bytes,
+            &[true, false, true, false, false, false],
+            true,
+            true,
+        );
+        write_bits(&mut bytes, &[false, true, false], false, true);
+        write_bits(&mut bytes, &[false, true], false, false);
+        write_vector(&mut bytes, &[0, 0]);
+        write_vector(&mut bytes, &[0; 8]);
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        let tail = bytes.len();
+        write_vector(&mut bytes, b"ab\0");
+        write_bits(&mut bytes, &[], false, false);
+        let tail_end = bytes.len();
+        write_vector(&mut bytes, &[]);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&MARISA_FLAGS.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(b"c\0");
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+
+        assert_eq!(
+            read_ocd2(&bytes).unwrap(),
+            [("ab".to_string(), vec!["c".to_string()])]
+        );
+
+        // A binary tail whose end flag lies past its bytes.
+        let mut binary = Vec::new();
+        write_vector(&mut binary, b"ab");
+        write_bits(&mut binary, &[false, false, true], false, false);
+        bytes.splice(tail..tail_end, binary);
+        assert!(matches!(read_ocd2(&bytes), Err(FormatError::Corrupted)));
+    }
+
+    #[test]
+    fn test_ocd2_next_trie() {
+        /// Writes a level without a tail whose nodes have the given children, as marisa-trie does.
+        fn write_level(bytes: &mut Vec<u8>, children: &[usize], bases: &[u8], links: &[bool]) {
+            let mut louds = vec![true, false];
+            for &count in children {
+                louds.extend(core::iter::repeat_n(true, count).chain([false]));
+            }
+            louds.push(false);
+
+            let mut terminals = children.iter().map(|&count| count == 0).collect::<Vec<_>>();
+            terminals.push(false);
+
+            write_bits(bytes, &louds, true, true);
+            write_bits(bytes, &terminals, false, true);
+            write_bits(bytes, links, false, false);
+            write_vector(bytes, bases);
+            write_vector(bytes, &[0; 8]);
+            bytes.extend_from_slice(&[0; 8]);
+            bytes.extend_from_slice(
+                &(links.iter().filter(|&&link| link).count() as u64).to_le_bytes(),
+            );
+            write_vector(bytes, &[]);
+            write_bits(bytes, &[], false, false);
+        }
+
+        // The keys "xab" and "xcb" share the edge "x", then end with the edges "ab" and "cb". As
+        // in the dictionaries of OpenCC, those are stored reversed in a next level as "ba" and
+        // "bc", which share the edge "b", and are linked to by the IDs of their nodes.
+        let mut bytes = HEADER.to_vec();
+        bytes.extend_from_slice(MARISA_HEADER);
+        write_level(
+            &mut bytes,
+            &[1, 2, 0, 0],
+            &[0, b'x', 2, 3],
+            &[false, false, true, true],
+        );
+        write_level(
+            &mut bytes,
+            &[1, 2, 0, 0],
+            &[0, b'b', b'a', b'c'],
+            &[false; 4],
+        );
+        write_vector(&mut bytes, &[]);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&(1u32 | 0x1000 | 0x20000 | 0x200).to_le_bytes());
+        write_vector(&mut bytes, &[]);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&(2u32 | 0x1000 | 0x20000 | 0x200).to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        bytes.extend_from_slice(b"1\0");
+        bytes.extend_from_slice(b"2\0");
+        for _ in 0..2 {
+            bytes.extend_from_slice(&1u16.to_le_bytes());
+            bytes.extend_from_slice(&2u16.to_le_bytes());
+        }
+
+        assert_eq!(
+            read_ocd2(&bytes).unwrap(),
+            [
+                ("xab".to_string(), vec!["1".to_string()]),
+                ("xcb".to_string(), vec!["2".to_string()])
+            ]
+        );
+
+        // A next level whose nodes claim to be their own children, so walking up never ends.
+        let louds = bytes
+            .windows(9)
+            .rposition(|window| window == [8, 0, 0, 0, 0, 0, 0, 0, 0b0011_0101])
+            .unwrap();
+        bytes[louds + 8] = 0b1010_1001;
+        assert!(matches!(read_ocd2(&bytes), Err(FormatError::Corrupted)));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::matcher::Matcher;
+use crate::trie::Trie;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use regex::Regex;
+
+/// Separators and CJK punctuation that end a path segment.
+#[cfg(feature = "std")]
+const SEGMENT: &str = r"[^\s/\\，。；：！？、（）「」『』“”‘’《》【】]+";
+
+/// Printable ASCII chars other than separators, for paths without a `~` or `.` marker.
+#[cfg(feature = "std")]
+const ASCII_SEGMENT: &str = r"[\x21-\x7E&&[^/\\]]+";
+
+/// A built-in detector of text that must not be converted.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detector {
+    /// URLs with a scheme, such as `https://example.com/`, or starting with `www.`.
+    Url,
+    /// Email addresses.
+    Email,
+    /// Unix paths starting with `~/`, `./` or `../`, Unix paths starting with `/` at the start of
+    /// the text or after whitespace or punctuation with at least two ASCII components, and
+    /// Windows paths starting with a drive letter. Components end at whitespace or CJK
+    /// punctuation, so slash-separated lists of words such as `简/繁/日` are not paths.
+    Path,
+    /// Markdown inline code between single backticks.
+    InlineCode,
+}
+
+#[cfg(feature = "std")]
+impl Detector {
+    fn regex(&self) -> Regex {
+        let pattern = match self {
+            Detector::Url => {
+                r"(?:[A-Za-z][A-Za-z0-9+.-]*://|www\.)[A-Za-z0-9\-._~:/?#\[\]@!$&'()*+,;=%]+"
+                    .to_string()
+            }
+            Detector::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)+".to_string(),
+            Detector::Path => format!(
+                r"(?:~|\.{{1,2}})(?:/{SEGMENT})+/?|\B(?:/{ASCII_SEGMENT}){{2,}}/?|[A-Za-z]:\\(?:{SEGMENT}\\?)*"
+            ),
+            Detector::InlineCode => r"`[^`\n]+`".to_string(),
+        };
+
+        Regex::new(&pattern).unwrap()
+    }
+}
+
+/// Regions of the input that a [`Converter`](crate::Converter) copies verbatim.
+///
+/// Text between protected regions is converted separately, so no phrase is matched across a
+/// protected region. Overlapping regions found by different patterns are merged.
+///
+/// Without the `std` feature, only listed terms can be protected.
+#[derive(Default)]
+pub struct Protection {
+    #[cfg(feature = "std")]
+    patterns: Vec<Regex>,
+    terms: Trie<String>,
+}
+
+impl Protection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Protects the matches of `pattern`.
+    #[cfg(feature = "std")]
+    pub fn pattern(self, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(self.regex(Regex::new(pattern)?))
+    }
+
+    /// Protects the matches of `regex`.
+    #[cfg(feature = "std")]
+    pub fn regex(mut self, regex: Regex) -> Self {
+        self.patterns.push(regex);
+        self
+    }
+
+    /// Protects the text found by `detector`.
+    #[cfg(feature = "std")]
+    pub fn detector(self, detector: Detector) -> Self {
+        self.regex(detector.regex())
+    }
+
+    /// Protects every occurrence of `term`, preferring the longest term at each position.
+    pub fn term(mut self, term: impl AsRef<str>) -> Self {
+        let term = term.as_ref();
+        self.terms.insert(term, term.to_string());
+        self
+    }
+
+    pub fn terms(self, terms: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        terms.into_iter().fold(self, Protection::term)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        #[cfg(feature = "std")]
+        if !self.patterns.is_empty() {
+            return false;
+        }
+
+        self.terms.is_empty()
+    }
+
+    /// Returns the length in chars of the longest protected term.
+    #[cfg(feature = "std")]
+    pub(crate) fn term_depth(&self) -> usize {
+        self.terms.depth()
+    }
+
+    /// Returns the sorted, disjoint byte ranges of `s` that are protected.
+    pub fn find(&self, s: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+
+        #[cfg(feature = "std")]
+        ranges.extend(
+            self.patterns
+                .iter()
+                .flat_map(|regex| regex.find_iter(s).map(|m| m.range()))
+                .filter(|range| !range.is_empty()),
+        );
+
+        if !self.terms.is_empty() {
+            let mut i = 0;
+            while let Some(c) = s[i..].chars().next() {
+                match self.terms.longest_match(&s[i..]) {
+                    Some((_, len)) => {
+                        ranges.push(i..i + len);
+                        i += len;
+                    }
+                    None => i += c.len_utf8(),
+                }
+            }
+        }
+
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        merged
+    }
+
+    /// Splits `s` into consecutive ranges, each marked whether it is protected.
+    pub(crate) fn split(&self, s: &str) -> Vec<(Range<usize>, bool)> {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+
+        for range in self.find(s) {
+            if start < range.start {
+                ranges.push((start..range.start, false));
+            }
+            start = range.end;
+            ranges.push((range, true));
+        }
+
+        if start < s.len() || ranges.is_empty() {
+            ranges.push((start..s.len(), false));
+        }
+
+        ranges
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "st"))]
+mod tests {
+    use super::*;
+    use crate::Converters;
+
+    #[test]
+    fn test_protection() {
+        let converter = Converters::S2T
+            .builder()
+            .protect(
+                Protection::new()
+                    .detector(Detector::Url)
+                    .detector(Detector::Email)
+                    .detector(Detector::Path)
+                    .detector(Detector::InlineCode)
+                    .term("头"),
+            )
+            .build();
+
+        assert_eq!(
+            converter.convert("访问https://example.com/a?b=1，或发邮件到a@example.com"),
+            "訪問https://example.com/a?b=1，或發郵件到a@example.com"
+        );
+        assert_eq!(
+            converter.convert("打开~/文档/简体.txt 和`简体`代码"),
+            "打開~/文档/简体.txt 和`简体`代碼"
+        );
+        assert_eq!(
+            converter.convert("支持简/繁/日，见 /usr/share/简体"),
+            "支持簡/繁/日，見 /usr/share/簡體"
+        );
+        assert_eq!(converter.convert("打开/简体/繁体"), "打開/簡體/繁體");
+
+        // "头发" is not matched across the protected term.
+        assert_eq!(converter.convert("头发"), "头發");
+
+        let (output, spans) = converter.convert_with_spans("头发");
+        assert_eq!(output, "头發");
+        assert_eq!(spans[0].source, 0..3);
+        assert_eq!(spans[0].target, 0..3);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::conv::Converter;
+use crate::span::{self, Candidates, Span};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A part of the input that converting forth and back does not restore, as returned by
+/// [`round_trip`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTripDifference {
+    /// The byte range of the part in the input.
+    pub source: Range<usize>,
+    /// The part of the input.
+    pub original: String,
+    /// The part converted forth.
+    pub converted: String,
+    /// The part converted forth and back.
+    pub restored: String,
+    /// The matches of the forward conversion in the part, each as its key and candidate values.
+    pub forward: Vec<(String, Vec<String>)>,
+    /// The matches of the backward conversion in the converted part.
+    pub backward: Vec<(String, Vec<String>)>,
+}
+
+/// Splits the spans of runs left unchanged into a span per char, so that they line up with the
+/// matches of another conversion.
+fn split_unchanged(spans: Vec<Span>, input: &str, output: &str) -> Vec<Span> {
+    let mut split = Vec::with_capacity(spans.len());
+
+    for span in spans {
+        if input[span.source.clone()] != output[span.target.clone()] {
+            split.push(span);
+            continue;
+        }
+
+        split.extend(
+            input[span.source.clone()]
+                .char_indices()
+                .map(|(i, c)| Span {
+                    source: span.source.start + i..span.source.start + i + c.len_utf8(),
+                    target: span.target.start + i..span.target.start + i + c.len_utf8(),
+                }),
+        );
+    }
+
+    split
+}
+
+/// Returns the matches in `text` whose source overlaps `range`.
+fn matches(
+    text: &str,
+    candidates: &[Candidates],
+    range: &Range<usize>,
+) -> Vec<(String, Vec<String>)> {
+    candidates
+        .iter()
+        .filter(|candidates| {
+            candidates.span.source.start < range.end && range.start < candidates.span.source.end
+        })
+        .map(|candidates| {
+            (
+                text[candidates.span.source.clone()].to_string(),
+                candidates.values.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Converts `s` with `forward` and the result with `backward`, e.g. `S2T` and `T2S`, and lists the
+/// parts of `s` that do not come back unchanged, in order.
+///
+/// Each part is the smallest run of `s` whose conversions line up on both ends, and lists the
+/// entries that matched in it in either direction.
+pub fn round_trip(
+    forward: &Converter,
+    backward: &Converter,
+    s: impl AsRef<str>,
+) -> Vec<RoundTripDifference> {
+    let original = s.as_ref();
+    let (converted, first) = forward.convert_with_spans(original);
+    let (restored, second) = backward.convert_with_spans(&converted);
+
+    if restored == original {
+        return Vec::new();
+    }
+
+    let first = split_unchanged(first, original, &converted);
+    let second = split_unchanged(second, &converted, &restored);
+
+    let (_, forward_candidates) = forward.convert_with_candidates(original);
+    let (_, backward_candidates) = backward.convert_with_candidates(&converted);
+
+    span::align(&first, &second)
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let joined = span::join(&first, &second, (a.clone(), b));
+            let middle = first[a.start].target.start..first[a.end - 1].target.end;
+
+            if original[joined.source.clone()] == restored[joined.target.clone()] {
+                return None;
+            }
+
+            Some(RoundTripDifference {
+                original: original[joined.source.clone()].to_string(),
+                converted: converted[middle.clone()].to_string(),
+                restored: restored[joined.target].to_string(),
+                forward: matches(original, &forward_candidates, &joined.source),
+                backward: matches(&converted, &backward_candidates, &middle),
+                source: joined.source,
+            })
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "st", feature = "ts"))]
+mod tests {
+    use super::*;
+    use crate::Converters;
+    use alloc::vec;
+
+    #[test]
+    fn test_round_trip() {
+        let s2t = Converters::S2T.new_converter();
+        let t2s = Converters::T2S.new_converter();
+
+        assert!(round_trip(&s2t, &t2s, "简繁转换").is_empty());
+
+        let input = "他的麵條";
+        let differences = round_trip(&t2s, &s2t, input);
+
+        assert_eq!(differences.len(), 1);
+        assert_eq!(&input[differences[0].source.clone()], "麵條");
+        assert_eq!(differences[0].converted, "面条");
+        assert_eq!(differences[0].restored, "麪條");
+        assert_eq!(
+            differences[0].forward[0],
+            ("麵".into(), vec!["面".into(), "麺".into()])
+        );
+        assert_eq!(
+            differences[0].backward,
+            [("面条".into(), vec!["麪條".into()])]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::matcher::Matcher;
+use crate::stage::Stage;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Maps a byte range of the input of a conversion to the byte range of the output it produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub source: Range<usize>,
+    pub target: Range<usize>,
+}
+
+impl Span {
+    /// Offsets the span by the starts of the runs of the input and output it was computed on.
+    pub(crate) fn shift(self, source: usize, target: usize) -> Span {
+        Span {
+            source: self.source.start + source..self.source.end + source,
+            target: self.target.start + target..self.target.end + target,
+        }
+    }
+}
+
+/// A match of a conversion together with its candidate values, the chosen one first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidates {
+    pub span: Span,
+    pub values: Vec<String>,
+}
+
+/// A span together with the candidate values of its match, if any.
+pub(crate) type Segment<V> = (Span, Option<Vec<V>>);
+
+/// Converts `input` with a single dictionary, recording a span for every match and every
+/// unmatched char.
+pub(crate) fn convert(dictionary: &dyn Matcher, input: &str) -> (String, Vec<Span>) {
+    let mut output = String::with_capacity(input.len());
+    let mut spans = Vec::new();
+    let mut consumed = 0;
+
+    while let Some(c) = input[consumed..].chars().next() {
+        let start = output.len();
+        let len = if let Some((value, len)) = dictionary.longest_match(&input[consumed..]) {
+            output.push_str(value);
+            len
+        } else {
+            output.push(c);
+            c.len_utf8()
+        };
+
+        spans.push(Span {
+            source: consumed..consumed + len,
+            target: start..output.len(),
+        });
+        consumed += len;
+    }
+
+    (output, spans)
+}
+
+/// Converts `input` with a stage that is not a dictionary, recording a span for every char of the
+/// unchanged prefix and suffix and one span for the changed run between them.
+pub(crate) fn convert_opaque(stage: &dyn Stage, input: &str) -> (String, Vec<Span>) {
+    let output = stage.apply(input).into_owned();
+
+    let mut prefix = input
+        .char_indices()
+        .zip(output.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(input.len().min(output.len()), |((i, _), _)| i);
+    let mut suffix = input[prefix..]
+        .chars()
+        .rev()
+        .zip(output[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+
+    // Widen a run that only inserts or deletes text by an unchanged char, so that every span
+    // covers some of both texts.
+    while input != output && (input.len() - suffix == prefix || output.len() - suffix == prefix) {
+        if let Some(c) = input[..prefix].chars().next_back() {
+            prefix -= c.len_utf8();
+        } else if let Some(c) = input[input.len() - suffix..].chars().next() {
+            suffix -= c.len_utf8();
+        } else {
+            break;
+        }
+    }
+
+    let mut spans = Vec::new();
+    for (i, c) in input[..prefix].char_indices() {
+        spans.push(Span {
+            source: i..i + c.len_utf8(),
+            target: i..i + c.len_utf8(),
+        });
+    }
+
+    let (source_end, target_end) = (input.len() - suffix, output.len() - suffix);
+    if prefix < source_end || prefix < target_end {
+        spans.push(Span {
+            source: prefix..source_end,
+            target: prefix..target_end,
+        });
+    }
+
+    for (i, c) in input[source_end..].char_indices() {
+        let (source, target) = (source_end + i, target_end + i);
+        spans.push(Span {
+            source: source..source + c.len_utf8(),
+            target: target..target + c.len_utf8(),
+        });
+    }
+
+    (output, spans)
+}
+
+/// Converts `input` with a single dictionary like [`convert`], also recording the candidate
+/// values of every match.
+pub(crate) fn convert_candidates<'a>(
+    dictionary: &'a dyn Matcher,
+    input: &str,
+) -> (String, Vec<Segment<&'a str>>) {
+    let mut output = String::with_capacity(input.len());
+    let mut segments = Vec::new();
+    let mut consumed = 0;
+
+    while let Some(c) = input[consumed..].chars().next() {
+        let start = output.len();
+        let (len, candidates) = match dictionary.candidates(&input[consumed..]) {
+            Some((candidates, len)) => {
+                output.push_str(candidates[0]);
+                (len, Some(candidates))
+            }
+            None => {
+                output.push(c);
+                (c.len_utf8(), None)
+            }
+        };
+
+        let span = Span {
+            source: consumed..consumed + len,
+            target: start..output.len(),
+        };
+        segments.push((span, candidates));
+        consumed += len;
+    }
+
+    (output, segments)
+}
+
+/// Groups the spans of two consecutive conversions until their boundaries in the intermediate
+/// text line up, returning the index ranges of each group.
+pub(crate) fn align(first: &[Span], second: &[Span]) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut groups = Vec::with_capacity(second.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < first.len() && j < second.len() {
+        let (start_i, start_j) = (i, j);
+        let (mut a, mut b) = (first[i].target.end, second[j].source.end);
+        i += 1;
+        j += 1;
+
+        while a != b {
+            if a < b && i < first.len() {
+                a = first[i].target.end;
+                i += 1;
+            } else if j < second.len() {
+                b = second[j].source.end;
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        groups.push((start_i..i, start_j..j));
+    }
+
+    if i < first.len() {
+        groups.push((i..first.len(), j..j));
+    } else if j < second.len() {
+        groups.push((i..i, j..second.len()));
+    }
+
+    groups
+}
+
+/// Joins a group of spans of two consecutive conversions into one span. A group without spans of
+/// one conversion is joined to an empty span at the end of its text, such as the text a stage
+/// inserted into empty input.
+pub(crate) fn join(first: &[Span], second: &[Span], (a, b): (Range<usize>, Range<usize>)) -> Span {
+    let source_end = first.last().map_or(0, |span| span.source.end);
+    let end = second.last().map_or(0, |span| span.target.end);
+
+    Span {
+        source: if a.is_empty() {
+            source_end..source_end
+        } else {
+            first[a.start].source.start..first[a.end - 1].source.end
+        },
+        target: if b.is_empty() {
+            end..end
+        } else {
+            second[b.start].target.start..second[b.end - 1].target.end
+        },
+    }
+}
+
+/// Composes the spans of two consecutive conversions.
+pub(crate) fn compose(first: &[Span], second: &[Span]) -> Vec<Span> {
+    align(first, second)
+        .into_iter()
+        .map(|group| join(first, second, group))
+        .collect()
+}
+
+/// Merges consecutive spans whose source text is unchanged in the target text.
+pub(crate) fn merge_unchanged(spans: Vec<Span>, input: &str, output: &str) -> Vec<Span> {
+    let unchanged = |span: &Span| input[span.source.clone()] == output[span.target.clone()];
+    let mut merged: Vec<Span> = Vec::with_capacity(spans.len());
+
+    for span in spans {
+        if let Some(last) = merged.last_mut()
+            && unchanged(last)
+            && unchanged(&span)
+        {
+            last.source.end = span.source.end;
+            last.target.end = span.target.end;
+        } else {
+            merged.push(span);
+        }
+    }
+
+    merged
+}
@@ -0,0 +1,174 @@
+use crate::matcher::Matcher;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::convert::Infallible;
+
+/// A step of the pipeline of a [`Converter`](crate::Converter), such as a dictionary, a
+/// normalization, punctuation rewriting or a glossary lookup backed by a database.
+///
+/// Every [`Matcher`], such as a [`Trie`](crate::Trie), is a stage. Other stages are opaque to the
+/// converter: they convert their whole input at once and are not fused. Their changes are mapped
+/// as one span from the first to the last changed char, they list no candidates, and
+/// [`ConvertReader`](crate::ConvertReader) and [`ConvertWriter`](crate::ConvertWriter) give them
+/// whole lines.
+pub trait Stage: Send + Sync {
+    /// Converts `input`, borrowing it if nothing changed.
+    fn apply<'s>(&self, input: &'s str) -> Cow<'s, str>;
+
+    /// Returns the stage as a dictionary if it is one.
+    fn as_matcher(&self) -> Option<&dyn Matcher> {
+        None
+    }
+}
+
+impl<M: Matcher> Stage for M {
+    fn apply<'s>(&self, input: &'s str) -> Cow<'s, str> {
+        match first_match(self, input) {
+            None => Cow::Borrowed(input),
+            Some(i) => {
+                let mut output = String::with_capacity(input.len());
+                output.push_str(&input[..i]);
+
+                let Ok(()) = write_converted(self, &input[i..], &mut |piece| {
+                    output.push_str(piece);
+                    Ok::<_, Infallible>(())
+                });
+                Cow::Owned(output)
+            }
+        }
+    }
+
+    fn as_matcher(&self) -> Option<&dyn Matcher> {
+        Some(self)
+    }
+}
+
+/// Returns the byte offset of the first match of `dictionary` in `s`.
+fn first_match(dictionary: &dyn Matcher, s: &str) -> Option<usize> {
+    s.char_indices()
+        .map(|(i, _)| i)
+        .find(|&i| dictionary.longest_match(&s[i..]).is_some())
+}
+
+/// Writes the conversion of `s` by `dictionary` in pieces, each run of unmatched chars as one
+/// piece.
+pub(crate) fn write_converted<E>(
+    dictionary: &dyn Matcher,
+    s: &str,
+    write: &mut impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut start = 0;
+    let mut i = 0;
+
+    while let Some(c) = s[i..].chars().next() {
+        if let Some((value, len)) = dictionary.longest_match(&s[i..]) {
+            if start < i {
+                write(&s[start..i])?;
+            }
+            write(value)?;
+            i += len;
+            start = i;
+        } else {
+            i += c.len_utf8();
+        }
+    }
+
+    if start < s.len() {
+        write(&s[start..])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "st"))]
+mod tests {
+    use super::*;
+    use crate::Converters;
+    use alloc::vec::Vec;
+
+    /// Rewrites ASCII commas as fullwidth commas.
+    struct Commas;
+
+    impl Stage for Commas {
+        fn apply<'s>(&self, input: &'s str) -> Cow<'s, str> {
+            match input.contains(',') {
+                true => Cow::Owned(input.replace(',', "，")),
+                false => Cow::Borrowed(input),
+            }
+        }
+    }
+
+    /// Replaces empty input with a placeholder.
+    struct Placeholder;
+
+    impl Stage for Placeholder {
+        fn apply<'s>(&self, input: &'s str) -> Cow<'s, str> {
+            match input.is_empty() {
+                true => Cow::Borrowed("（空）"),
+                false => Cow::Borrowed(input),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stage() {
+        let converter = Converters::S2T.builder().insert_before(0, Commas).build();
+
+        assert_eq!(converter.convert("简体,繁体"), "簡體，繁體");
+        assert_eq!(
+            converter.fuse().stages().collect::<Vec<_>>(),
+            ["custom", "S2T"]
+        );
+
+        let converter = Converters::S2T
+            .builder()
+            .push_named("commas", Commas)
+            .build();
+        let (output, spans) = converter.convert_with_spans("简,繁");
+        assert_eq!(output, "簡，繁");
+        assert_eq!(spans[1].source, 3..4);
+        assert_eq!(spans[1].target, 3..6);
+
+        // Text inserted into empty input comes from an empty span at its start.
+        let converter = Converters::S2T.builder().push(Placeholder).build();
+        let (output, spans) = converter.convert_with_spans("");
+        assert_eq!(output, "（空）");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].source, 0..0);
+        assert_eq!(spans[0].target, 0..output.len());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_shared_dictionary() {
+        use crate::{Converter, Trie};
+        use alloc::sync::Arc;
+        use std::thread;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Converter>();
+
+        let glossary = Arc::new(Trie::from_iter([("云服务", "雲端服務")]));
+        let converters = [
+            Converter::new([glossary.clone()]),
+            Converters::S2T
+                .builder()
+                .overlay(0, glossary.clone())
+                .build(),
+        ];
+
+        let outputs = thread::scope(|scope| {
+            converters
+                .iter()
+                .map(|converter| scope.spawn(|| converter.convert("云服务器")))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(outputs, ["雲端服務器", "雲端服務器"]);
+
+        drop(converters);
+        assert_eq!(Arc::strong_count(&glossary), 1);
+    }
+}
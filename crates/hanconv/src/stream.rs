@@ -0,0 +1,418 @@
+use crate::conv::Converter;
+use crate::matcher::Matcher;
+use crate::protect::Protection;
+use crate::stage::Stage;
+use std::io::{self, BufRead, Read, Write};
+use std::str;
+
+/// The length in bytes from which a line held back by [`Pipeline`] is split before it ends.
+const LINE_LIMIT: usize = 64 * 1024;
+
+/// Converts text fed in chunks through every stage of a converter.
+///
+/// Each dictionary holds back the chars that are too close to the end of its input to tell its
+/// longest match, which is fewer than its depth, until more input arrives or the input ends.
+/// Other stages hold back the last line until it is complete. If the converter protects regions
+/// of its input, whole lines are held back instead, and protected regions are found within each
+/// line. Lines longer than [`LINE_LIMIT`] are split after their last whitespace or CJK
+/// punctuation outside protected regions, or where they are if they have none, so memory use
+/// stays bounded.
+struct Pipeline<'a> {
+    converter: &'a Converter,
+    pending: Vec<String>,
+    line: String,
+}
+
+impl<'a> Pipeline<'a> {
+    fn new(converter: &'a Converter) -> Self {
+        Pipeline {
+            converter,
+            pending: vec![String::new(); converter.stages.len()],
+            line: String::new(),
+        }
+    }
+
+    fn push(&mut self, input: &str, output: &mut String, finish: bool) {
+        if !self.converter.protection.is_empty() {
+            self.line.push_str(input);
+
+            let end = line_end(&self.line, finish, Some(&self.converter.protection));
+            output.push_str(&self.converter.convert(&self.line[..end]));
+            self.line.drain(..end);
+
+            return;
+        }
+
+        let mut input = input.to_string();
+
+        for (stage, pending) in self.converter.stages.iter().zip(&mut self.pending) {
+            pending.push_str(&input);
+            input.clear();
+
+            match stage.as_matcher() {
+                Some(dictionary) => convert_chunk(dictionary, pending, &mut input, finish),
+                None => convert_lines(stage.as_ref(), pending, &mut input, finish),
+            }
+        }
+
+        output.push_str(&input);
+    }
+}
+
+fn convert_chunk(
+    dictionary: &dyn Matcher,
+    pending: &mut String,
+    output: &mut String,
+    finish: bool,
+) {
+    let limit = match dictionary.depth() {
+        _ if finish => Some(pending.len()),
+        0 => Some(pending.len()),
+        depth => pending.char_indices().rev().nth(depth - 1).map(|(i, _)| i),
+    };
+
+    let mut consumed = 0;
+
+    if let Some(limit) = limit {
+        while consumed <= limit
+            && let Some(c) = pending[consumed..].chars().next()
+        {
+            if let Some((value, len)) = dictionary.longest_match(&pending[consumed..]) {
+                output.push_str(value);
+                consumed += len;
+            } else {
+                output.push(c);
+                consumed += c.len_utf8();
+            }
+        }
+    }
+
+    pending.drain(..consumed);
+}
+
+/// Returns the end of the complete lines of `s`, or of the part of a line longer than
+/// [`LINE_LIMIT`] up to its last whitespace or CJK punctuation.
+///
+/// With a `protection`, a long line is not split inside a protected region, nor within the length
+/// of the longest protected term from its end, where a term may still be incomplete.
+fn line_end(s: &str, finish: bool, protection: Option<&Protection>) -> usize {
+    match s.rfind('\n') {
+        _ if finish => s.len(),
+        Some(i) => i + 1,
+        None if s.len() < LINE_LIMIT => 0,
+        None => {
+            let (protected, depth) = match protection {
+                Some(protection) => (protection.find(s), protection.term_depth()),
+                None => (Vec::new(), 0),
+            };
+            let limit = match depth {
+                0 => s.len(),
+                depth => s.char_indices().rev().nth(depth - 1).map_or(0, |(i, _)| i),
+            };
+
+            s.char_indices()
+                .rev()
+                .filter(|&(_, c)| is_break(c))
+                .map(|(i, c)| i + c.len_utf8())
+                .find(|&end| {
+                    end <= limit
+                        && !protected
+                            .iter()
+                            .any(|range| range.start < end && end < range.end)
+                })
+                .unwrap_or(s.len())
+        }
+    }
+}
+
+/// Whether a long line can be split after `c`. ASCII punctuation is excluded, as it occurs in the
+/// URLs, emails and paths found by protection.
+fn is_break(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '\u{3001}'..='\u{303F}' | '\u{FF01}'..='\u{FF0F}' | '\u{FF1A}'..='\u{FF20}'
+        )
+}
+
+/// Converts the complete lines of `pending` with a stage that is not a dictionary.
+fn convert_lines(stage: &dyn Stage, pending: &mut String, output: &mut String, finish: bool) {
+    let end = line_end(pending, finish, None);
+
+    if end > 0 {
+        output.push_str(&stage.apply(&pending[..end]));
+        pending.drain(..end);
+    }
+}
+
+/// Splits `bytes` into the longest valid UTF-8 prefix and the length of an incomplete char at the
+/// end.
+fn decode(bytes: &[u8]) -> io::Result<(&str, usize)> {
+    match str::from_utf8(bytes) {
+        Ok(s) => Ok((s, 0)),
+        Err(err) if err.error_len().is_none() => {
+            let (valid, rest) = bytes.split_at(err.valid_up_to());
+            Ok((str::from_utf8(valid).unwrap(), rest.len()))
+        }
+        Err(err) => Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+    }
+}
+
+fn incomplete() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not end with a complete UTF-8 char",
+    )
+}
+
+/// A reader that converts the UTF-8 text read from an inner reader.
+///
+/// Phrases split across reads are converted the same way as by [`Converter::convert`], except
+/// that protected regions and the input of stages other than dictionaries do not span lines, and
+/// lines longer than 64 KiB are split after their last whitespace or CJK punctuation outside
+/// protected regions.
+pub struct ConvertReader<'a, R: Read> {
+    inner: R,
+    pipeline: Pipeline<'a>,
+    input: Vec<u8>,
+    output: String,
+    position: usize,
+    eof: bool,
+}
+
+impl<'a, R: Read> ConvertReader<'a, R> {
+    pub fn new(converter: &'a Converter, inner: R) -> Self {
+        ConvertReader {
+            inner,
+            pipeline: Pipeline::new(converter),
+            input: Vec::new(),
+            output: String::new(),
+            position: 0,
+            eof: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> BufRead for ConvertReader<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let mut chunk = [0u8; 8192];
+
+        while self.position == self.output.len() && !self.eof {
+            self.output.clear();
+            self.position = 0;
+
+            let n = self.inner.read(&mut chunk)?;
+            self.input.extend_from_slice(&chunk[..n]);
+
+            if n == 0 {
+                self.eof = true;
+                if !self.input.is_empty() {
+                    return Err(incomplete());
+                }
+            }
+
+            let (s, incomplete) = decode(&self.input)?;
+            self.pipeline.push(s, &mut self.output, self.eof);
+            self.input.drain(..self.input.len() - incomplete);
+        }
+
+        Ok(&self.output.as_bytes()[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.output.len());
+    }
+}
+
+impl<R: Read> Read for ConvertReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+/// A writer that converts UTF-8 text before writing it to an inner writer.
+///
+/// Phrases split across writes are converted the same way as by [`Converter::convert`], except
+/// that protected regions and the input of stages other than dictionaries do not span lines, and
+/// lines longer than 64 KiB are split after their last whitespace or CJK punctuation outside
+/// protected regions. The chars held back for them are written by
+/// [`finish`](ConvertWriter::finish), or when the writer is dropped, ignoring errors.
+pub struct ConvertWriter<'a, W: Write> {
+    inner: Option<W>,
+    pipeline: Pipeline<'a>,
+    input: Vec<u8>,
+    output: String,
+}
+
+impl<'a, W: Write> ConvertWriter<'a, W> {
+    pub fn new(converter: &'a Converter, inner: W) -> Self {
+        ConvertWriter {
+            inner: Some(inner),
+            pipeline: Pipeline::new(converter),
+            input: Vec::new(),
+            output: String::new(),
+        }
+    }
+
+    fn push(&mut self, finish: bool) -> io::Result<()> {
+        let (s, incomplete) = decode(&self.input)?;
+        if finish && incomplete != 0 {
+            return Err(self::incomplete());
+        }
+
+        self.pipeline.push(s, &mut self.output, finish);
+        self.input.drain(..self.input.len() - incomplete);
+
+        if let Some(inner) = &mut self.inner {
+            inner.write_all(self.output.as_bytes())?;
+        }
+        self.output.clear();
+
+        Ok(())
+    }
+
+    /// Converts and writes the held back chars, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.push(true)?;
+
+        let mut inner = self.inner.take().unwrap();
+        inner.flush()?;
+
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for ConvertWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Invalid bytes are not kept, so the next write can continue after the error.
+        let len = self.input.len();
+        self.input.extend_from_slice(buf);
+        if let Err(err) = decode(&self.input) {
+            self.input.truncate(len);
+            return Err(err);
+        }
+
+        self.push(false)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for ConvertWriter<'_, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.push(true);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "st", feature = "tw"))]
+mod tests {
+    use super::*;
+    use crate::{Converters, Dictionary, Protection};
+
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let text = Dictionary::STPhrases
+            .iter()
+            .take(2000)
+            .flat_map(|(key, value)| [key, value])
+            .collect::<String>();
+        let converter = Converters::S2TWP.new_converter();
+        let expected = converter.convert(&text);
+
+        let mut writer = ConvertWriter::new(&converter, Vec::new());
+        for chunk in text.as_bytes().chunks(5) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            expected
+        );
+
+        let mut output = String::new();
+        ConvertReader::new(&converter, Trickle(text.as_bytes()))
+            .read_to_string(&mut output)
+            .unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_stream_long_line() {
+        let text = "简体和繁体，".repeat(10_000);
+        let converter = Converters::S2T
+            .builder()
+            .protect(Protection::new().term("简体"))
+            .build();
+
+        // A line without a line break is not held back whole.
+        let mut reader = ConvertReader::new(&converter, text.as_bytes());
+        assert!(!reader.fill_buf().unwrap().is_empty());
+        assert!(!reader.into_inner().is_empty());
+
+        let mut output = String::new();
+        ConvertReader::new(&converter, text.as_bytes())
+            .read_to_string(&mut output)
+            .unwrap();
+        assert_eq!(output, converter.convert(&text));
+    }
+
+    #[test]
+    fn test_stream_long_line_protected() {
+        let unit = "简体 字，";
+        let text = unit.repeat(6_000);
+        let converter = Converters::S2T
+            .builder()
+            .protect(Protection::new().term("体 字"))
+            .build();
+
+        // The first write ends a long line within a protected term with a space, past its last
+        // break outside protected regions.
+        let end = (LINE_LIMIT / unit.len() + 1) * unit.len() - "，".len();
+        let mut writer = ConvertWriter::new(&converter, Vec::new());
+        writer.write_all(&text.as_bytes()[..end]).unwrap();
+        writer.write_all(&text.as_bytes()[end..]).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            converter.convert(&text)
+        );
+    }
+
+    #[test]
+    fn test_stream_invalid_utf8() {
+        let converter = Converters::S2T.new_converter();
+        let mut writer = ConvertWriter::new(&converter, Vec::new());
+
+        writer.write_all("简".as_bytes()).unwrap();
+        assert_eq!(
+            writer.write(b"\xFF").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        writer.write_all("体".as_bytes()).unwrap();
+        assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), "簡體");
+    }
+}
@@ -1,7 +1,28 @@
+use crate::compact::FormatError;
+use crate::dict;
+use crate::matcher::Matcher;
+use crate::ocd2;
+#[cfg(feature = "std")]
 use ahash::AHashMap;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::Chars;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+type Children<T> = AHashMap<char, Node<T>>;
+#[cfg(not(feature = "std"))]
+type Children<T> = alloc::collections::BTreeMap<char, Node<T>>;
 
 pub struct Node<T> {
-    children: Option<AHashMap<char, Node<T>>>,
+    children: Option<Children<T>>,
     value: Option<T>,
 }
 
@@ -14,6 +35,7 @@ impl<T> Default for Node<T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> From<Node<T>> for AHashMap<String, T> {
     fn from(node: Node<T>) -> Self {
         let mut map = AHashMap::new();
@@ -39,9 +61,58 @@ impl<T> From<Node<T>> for AHashMap<String, T> {
     }
 }
 
+impl<T> Node<T> {
+    fn child(&self, c: char) -> Option<&Node<T>> {
+        self.children.as_ref()?.get(&c)
+    }
+
+    /// Returns the children sorted by char.
+    fn sorted_children(&self) -> Vec<(char, &Node<T>)> {
+        let mut children = self
+            .children
+            .iter()
+            .flatten()
+            .map(|(&c, node)| (c, node))
+            .collect::<Vec<_>>();
+        children.sort_unstable_by_key(|&(c, _)| c);
+        children
+    }
+
+    /// Returns the length in chars of the longest path below the node.
+    fn height(&self) -> usize {
+        self.children
+            .iter()
+            .flatten()
+            .map(|(_, node)| node.height() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Removes the value at the end of `chars`, pruning the nodes left without values.
+    fn remove(&mut self, mut chars: Chars) -> Option<T> {
+        let Some(c) = chars.next() else {
+            return self.value.take();
+        };
+
+        let children = self.children.as_mut()?;
+        let child = children.get_mut(&c)?;
+        let value = child.remove(chars)?;
+
+        if child.value.is_none() && child.children.is_none() {
+            children.remove(&c);
+            if children.is_empty() {
+                self.children = None;
+            }
+        }
+
+        Some(value)
+    }
+}
+
 pub struct Trie<T> {
     root: Node<T>,
     depth: usize,
+    len: usize,
 }
 
 impl<T> Default for Trie<T> {
@@ -49,6 +120,7 @@ impl<T> Default for Trie<T> {
         Trie {
             root: Default::default(),
             depth: Default::default(),
+            len: Default::default(),
         }
     }
 }
@@ -57,6 +129,15 @@ impl<T> Trie<T> {
     pub fn get_depth(&self) -> usize {
         self.depth
     }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 impl<T> Trie<T> {
@@ -65,6 +146,13 @@ impl<T> Trie<T> {
             return;
         }
 
+        if self.node_mut(key).value.replace(value).is_none() {
+            self.len += 1;
+        }
+    }
+
+    /// Returns the node at the end of `key`, inserting the missing nodes on its path.
+    fn node_mut(&mut self, key: &str) -> &mut Node<T> {
         let mut node = &mut self.root;
         let mut depth = 0usize;
 
@@ -77,7 +165,143 @@ impl<T> Trie<T> {
         if depth > self.depth {
             self.depth = depth;
         }
-        node.value = Some(value);
+
+        node
+    }
+
+    /// Removes a key and returns its value.
+    pub fn remove(&mut self, key: &str) -> Option<T> {
+        let value = self.root.remove(key.chars())?;
+        self.len -= 1;
+
+        if key.chars().count() == self.depth {
+            self.depth = self.root.height();
+        }
+
+        Some(value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut T> {
+        let mut node = &mut self.root;
+
+        for c in key.chars() {
+            node = node.children.as_mut()?.get_mut(&c)?;
+        }
+
+        node.value.as_mut()
+    }
+
+    /// Returns the entry of a key for in-place insertion or update, or `None` if the key is empty,
+    /// as empty keys cannot be inserted.
+    pub fn entry(&mut self, key: &str) -> Option<Entry<'_, T>> {
+        if key.is_empty() {
+            return None;
+        }
+
+        let key = key.to_string();
+        Some(match self.get(&key) {
+            Some(_) => Entry::Occupied(OccupiedEntry {
+                value: self.get_mut(&key)?,
+                key,
+            }),
+            None => Entry::Vacant(VacantEntry { trie: self, key }),
+        })
+    }
+}
+
+/// An entry of a [`Trie`], which may or may not have a value.
+pub enum Entry<'a, T> {
+    Occupied(OccupiedEntry<'a, T>),
+    Vacant(VacantEntry<'a, T>),
+}
+
+/// An entry of a [`Trie`] that has a value.
+pub struct OccupiedEntry<'a, T> {
+    key: String,
+    value: &'a mut T,
+}
+
+/// An entry of a [`Trie`] without a value.
+pub struct VacantEntry<'a, T> {
+    trie: &'a mut Trie<T>,
+    key: String,
+}
+
+impl<'a, T> Entry<'a, T> {
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Calls `f` with the value if there is one.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut T)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Inserts `default` if there is no value, and returns the value.
+    pub fn or_insert(self, default: T) -> &'a mut T {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if there is no value, and returns the value.
+    pub fn or_insert_with(self, default: impl FnOnce() -> T) -> &'a mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Inserts the default value if there is no value, and returns the value.
+    pub fn or_default(self) -> &'a mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+}
+
+impl<'a, T> OccupiedEntry<'a, T> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn get(&self) -> &T {
+        self.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value
+    }
+
+    /// Returns the value with the lifetime of the trie.
+    pub fn into_mut(self) -> &'a mut T {
+        self.value
+    }
+
+    /// Replaces the value and returns the old one.
+    pub fn insert(&mut self, value: T) -> T {
+        core::mem::replace(self.value, value)
+    }
+}
+
+impl<'a, T> VacantEntry<'a, T> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn into_key(self) -> String {
+        self.key
+    }
+
+    /// Inserts the value and returns it.
+    pub fn insert(self, value: T) -> &'a mut T {
+        self.trie.len += 1;
+        self.trie.node_mut(&self.key).value.insert(value)
     }
 }
 
@@ -114,6 +338,50 @@ impl<T> Trie<T> {
         true
     }
 
+    fn find(&self, prefix: &str) -> Option<&Node<T>> {
+        prefix.chars().try_fold(&self.root, |node, c| node.child(c))
+    }
+
+    /// Iterates over the keys and values, sorted by key.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: vec![(String::new(), &self.root)],
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = String> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Iterates over the keys starting with `prefix` and their values, sorted by key.
+    pub fn iter_prefix(&self, prefix: &str) -> Iter<'_, T> {
+        Iter {
+            stack: self
+                .find(prefix)
+                .map(|node| (prefix.to_string(), node))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Returns the longest prefix of `s` that is also a prefix of some key.
+    pub fn longest_common_prefix<'s>(&self, s: &'s str) -> &'s str {
+        let mut node = &self.root;
+
+        for (i, c) in s.char_indices() {
+            match node.child(c) {
+                Some(child) => node = child,
+                None => return &s[..i],
+            }
+        }
+
+        s
+    }
+
     pub fn r#match(&self, chars: impl Iterator<Item = char>) -> Option<(&T, usize)> {
         let mut node = &self.root;
         let mut result = None;
@@ -136,6 +404,40 @@ impl<T> Trie<T> {
     }
 }
 
+/// An iterator over the keys and values of a [`Trie`], sorted by key.
+pub struct Iter<'a, T> {
+    stack: Vec<(String, &'a Node<T>)>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (String, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, node)) = self.stack.pop() {
+            for (c, child) in node.sorted_children().into_iter().rev() {
+                let mut key = key.clone();
+                key.push(c);
+                self.stack.push((key, child));
+            }
+
+            if let Some(value) = &node.value {
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Trie<T> {
+    type Item = (String, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl<T: AsRef<str>> Trie<T> {
     pub fn convert(&self, input: impl AsRef<str>) -> String {
         let input = input.as_ref();
@@ -157,12 +459,68 @@ impl<T: AsRef<str>> Trie<T> {
     }
 }
 
+impl Trie<String> {
+    /// Builds a trie from text in the OpenCC dictionary format, mapping each key to its first value.
+    pub fn from_text(text: &str) -> Self {
+        dict::parse(text)
+            .map(|(key, value)| (key, value.to_string()))
+            .collect()
+    }
+
+    /// Builds a trie from a file in the OpenCC dictionary format.
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::from_text(&fs::read_to_string(path)?))
+    }
+
+    /// Builds a trie from an OpenCC `.ocd2` dictionary, mapping each key to its first value.
+    pub fn from_ocd2(bytes: &[u8]) -> Result<Self, FormatError> {
+        Ok(ocd2::read_ocd2(bytes)?
+            .into_iter()
+            .filter_map(|(key, values)| Some((key, values.into_iter().next()?)))
+            .collect())
+    }
+}
+
+impl<T: AsRef<str> + Send + Sync> Matcher for Trie<T> {
+    fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn longest_match(&self, s: &str) -> Option<(&str, usize)> {
+        let mut node = &self.root;
+        let mut result = None;
+
+        for (i, c) in s.char_indices().take(self.depth) {
+            if let Some(children) = &node.children
+                && let Some(child) = children.get(&c)
+            {
+                node = child;
+
+                if let Some(value) = node.value.as_ref() {
+                    result = Some((value.as_ref(), i + c.len_utf8()));
+                }
+            } else {
+                return result;
+            }
+        }
+
+        result
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (String, &str)> + '_> {
+        Box::new(self.iter().map(|(key, value)| (key, value.as_ref())))
+    }
+}
+
+#[cfg(feature = "std")]
 impl<T> From<Trie<T>> for AHashMap<String, T> {
     fn from(trie: Trie<T>) -> Self {
         trie.root.into()
     }
 }
 
+#[cfg(feature = "std")]
 impl<K: AsRef<str>, V> From<AHashMap<K, V>> for Trie<V> {
     fn from(map: AHashMap<K, V>) -> Self {
         let mut trie = Trie::default();
@@ -200,4 +558,49 @@ mod tests {
         assert_eq!(trie.get("一分钟"), Some("一分鐘").as_ref());
         assert_eq!(trie.convert("一分钟"), "一分鐘");
     }
+
+    #[test]
+    fn test_trie_api() {
+        let mut trie = Trie::from_iter([("一分钟", 1), ("一", 2), ("一分", 3), ("二", 4)]);
+
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            [
+                ("一".to_string(), &2),
+                ("一分".to_string(), &3),
+                ("一分钟".to_string(), &1),
+                ("二".to_string(), &4),
+            ]
+        );
+        assert_eq!(trie.iter_prefix("一分").count(), 2);
+        assert_eq!(trie.iter_prefix("三").count(), 0);
+        assert_eq!(trie.longest_common_prefix("一分半"), "一分");
+
+        *trie.entry("二").unwrap().or_insert(0) += 1;
+        trie.entry("三")
+            .unwrap()
+            .and_modify(|value| *value += 1)
+            .or_insert(6);
+        assert_eq!(trie.get("二"), Some(&5));
+        assert_eq!(trie.get("三"), Some(&6));
+
+        match trie.entry("三").unwrap() {
+            Entry::Occupied(mut entry) => assert_eq!(entry.insert(7), 6),
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match trie.entry("三十").unwrap() {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Vacant(entry) => *entry.insert(30) += 1,
+        }
+        assert_eq!(trie.get("三"), Some(&7));
+        assert_eq!(trie.get("三十"), Some(&31));
+        assert_eq!(trie.remove("三十"), Some(31));
+        assert!(trie.entry("").is_none());
+
+        assert_eq!(trie.remove("一分钟"), Some(1));
+        assert_eq!(trie.remove("一分钟"), None);
+        assert_eq!(trie.get_depth(), 2);
+        assert_eq!(trie.len(), 4);
+        assert!(!trie.starts_with("一分钟"));
+    }
 }
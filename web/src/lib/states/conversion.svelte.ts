@@ -1,11 +1,10 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Conversion } from "../../ui/ConversionSelector.svelte";
 
 export function createConversionState() {
   let inputText = $state("");
   let outputText = $state("");
   let isConverting = $state(false);
-  let selectedConversion: Conversion = $state("s2t");
+  let selectedConversion = $state("s2t");
 
   let timer: ReturnType<typeof setTimeout> | undefined;
   let latestConversionId = 0;
@@ -66,7 +65,7 @@ export function createConversionState() {
 
       const start = performance.now();
       try {
-        const result = await invoke<string>(conversion, { s: text });
+        const result = await invoke<string>("convert", { conversion, s: text });
         const end = performance.now();
         console.debug(
           `Conversion of ${text.length} chars took ${(end - start).toFixed(2)}ms`,